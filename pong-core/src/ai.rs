@@ -0,0 +1,223 @@
+use core::cmp;
+
+use crate::config::{Difficulty, GameConfig};
+use crate::game_state::{paddle_face, Player};
+use crate::pongball::{bounce_off_walls, Fixed, PongBall};
+use crate::pongbar::{Key, PongBar};
+use crate::rng::Rng;
+use crate::screen::BUFFER_HEIGHT;
+
+/// How a `Difficulty` plays
+pub struct AiProfile {
+    /// Milliseconds between the ball changing course and the CPU reacting to it
+    pub reaction_ms: u32,
+    /// Quarter cells the bar may move per step
+    pub max_speed: u8,
+    /// Up to this many rows of error are added to the predicted intercept
+    pub aim_error: u8,
+    /// Hit the ball with the outer zone of the bar to speed it up
+    pub aims_for_edges: bool,
+}
+
+pub fn profile(difficulty: Difficulty) -> AiProfile {
+    match difficulty {
+        Difficulty::Easy => AiProfile {
+            reaction_ms: 500,
+            max_speed: 1,
+            aim_error: 4,
+            aims_for_edges: false,
+        },
+        Difficulty::Normal => AiProfile {
+            reaction_ms: 280,
+            max_speed: 2,
+            aim_error: 2,
+            aims_for_edges: false,
+        },
+        Difficulty::Hard => AiProfile {
+            reaction_ms: 110,
+            max_speed: 3,
+            aim_error: 1,
+            aims_for_edges: true,
+        },
+        Difficulty::Perfect => AiProfile {
+            reaction_ms: 0,
+            max_speed: 6,
+            aim_error: 0,
+            aims_for_edges: true,
+        },
+    }
+}
+
+/// Brain of a CPU controlled `PongBar`
+pub struct CpuPlayer {
+    player: Player,
+    // Row the middle of the bar is heading to
+    target: i16,
+    // `speed.dx` the current target was planned for
+    // it only changes on a paddle hit or a new serve
+    seen_dx: Fixed,
+    noticed_at: u32,
+    planned: bool,
+    // Quarter cells moved that didn't add up to a full cell yet
+    progress: u8,
+}
+
+impl CpuPlayer {
+    pub fn new(player: Player) -> Self {
+        Self {
+            player,
+            target: BUFFER_HEIGHT as i16 / 2,
+            seen_dx: Fixed::from_num(0),
+            noticed_at: 0,
+            planned: false,
+            progress: 0,
+        }
+    }
+
+    /// Moves `bar` towards the row where `ball` is going to cross its column.
+    /// While the ball moves away it drifts back to the middle.
+    /// `now` is in milliseconds, see `Clock`.
+    pub fn steer(
+        &mut self,
+        bar: &mut PongBar,
+        ball: &PongBall,
+        config: &GameConfig,
+        now: u32,
+        rng: &mut impl Rng,
+    ) {
+        let profile = profile(config.difficulty());
+        let half_height = config.paddle_half_height();
+
+        if ball.speed.dx != self.seen_dx {
+            self.seen_dx = ball.speed.dx;
+            self.noticed_at = now;
+            self.planned = false;
+        }
+
+        if !self.planned {
+            if now.wrapping_sub(self.noticed_at) < profile.reaction_ms {
+                // Still reacting
+                return;
+            }
+            self.target = self.plan(ball, &profile, half_height, rng);
+            self.planned = true;
+        }
+
+        self.progress += profile.max_speed;
+        let cells = self.progress / 4;
+        self.progress %= 4;
+
+        for _ in 0..cells {
+            let bar_row = bar.position.y as i16;
+            match self.target.cmp(&bar_row) {
+                cmp::Ordering::Less => bar.shift(Key::Up, half_height),
+                cmp::Ordering::Greater => bar.shift(Key::Down, half_height),
+                cmp::Ordering::Equal => break,
+            }
+        }
+    }
+
+    fn plan(&self, ball: &PongBall, profile: &AiProfile, half_height: u8, rng: &mut impl Rng) -> i16 {
+        let intercept = match predict_intercept(ball, paddle_face(self.player)) {
+            Some(y) => y.floor().to_num::<i16>(),
+            None => return BUFFER_HEIGHT as i16 / 2,
+        };
+
+        let mut target = intercept + aim_noise(profile.aim_error, rng);
+
+        if profile.aims_for_edges {
+            // Take the ball with the outer zone (`diff == 2`) on the side
+            // that keeps the bar closer to the middle
+            if target < BUFFER_HEIGHT as i16 / 2 {
+                target += half_height as i16;
+            } else {
+                target -= half_height as i16;
+            }
+        }
+
+        // Noise on top of the edge offset must not take the bar past the ball
+        let reach = half_height as i16;
+        target.clamp(intercept - reach, intercept + reach)
+    }
+}
+
+// "Random" offset between -max_error and max_error
+fn aim_noise(max_error: u8, rng: &mut impl Rng) -> i16 {
+    if max_error == 0 {
+        return 0;
+    }
+    let random_u8 = rng.next_u8();
+    let range = 2 * max_error as u16 + 1;
+    (random_u8 as u16 % range) as i16 - max_error as i16
+}
+
+/// Row at which `ball` is going to reach `column`, walls included.
+/// Returns `None` if the ball is moving away from `column`.
+pub fn predict_intercept(ball: &PongBall, column: Fixed) -> Option<Fixed> {
+    let dx = ball.speed.dx;
+    if dx == Fixed::from_num(0) {
+        return None;
+    }
+
+    // Number of steps until the ball reaches the column
+    let steps = (column - ball.position.x) / dx;
+    if steps.is_negative() {
+        return None;
+    }
+
+    // Every bounce mirrors the path, so the real row is found by
+    // folding the straight line back into the field
+    let unfolded_y = ball.position.y + ball.speed.dy * steps;
+    Some(bounce_off_walls(unfolded_y, ball.speed.dy).0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pongball::{to_cell, BallPosition, Speed};
+
+    // Always gives the same noise
+    struct FixedRng(u8);
+
+    impl Rng for FixedRng {
+        fn next_u8(&mut self) -> u8 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn hard_never_misses_a_reachable_ball() {
+        let mut config = GameConfig::default();
+        config.set_difficulty(Difficulty::Hard);
+        let half_height = config.paddle_half_height() as i16;
+        let face = paddle_face(Player::Player2);
+
+        for &dy in &[0.0, 0.3, -0.45, 0.8] {
+            for row in 2..23 {
+                // Every noise the rng can give
+                for random_u8 in 0..3 {
+                    let position = BallPosition { x: Fixed::from_num(40), y: Fixed::from_num(row) };
+                    let mut ball = PongBall {
+                        last_pos: position,
+                        position,
+                        speed: Speed { dx: Fixed::from_num(1), dy: Fixed::from_num(dy) },
+                    };
+                    let intercept = to_cell(predict_intercept(&ball, face).unwrap()) as i16;
+                    let mut cpu = CpuPlayer::new(Player::Player2);
+                    let mut bar = PongBar::new(Player::Player2);
+
+                    // Almost 40 steps to get there, plenty for any row
+                    let mut now = 0;
+                    while ball.position.x < face {
+                        cpu.steer(&mut bar, &ball, &config, now, &mut FixedRng(random_u8));
+                        ball.move_ball();
+                        now += 1000 / config.physics_rate();
+                    }
+
+                    let diff = (intercept - bar.position.y as i16).abs();
+                    assert!(diff <= half_height, "dy {} row {} noise {}: off by {}", dy, row, random_u8, diff);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use pc_keyboard::KeyCode;
+
+/// What a key does while a match is running
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Action {
+    P1Up,
+    P1Down,
+    P2Up,
+    P2Down,
+    Pause,
+    Menu,
+    Rematch,
+}
+
+pub const ACTIONS: [Action; 7] = [
+    Action::P1Up,
+    Action::P1Down,
+    Action::P2Up,
+    Action::P2Down,
+    Action::Pause,
+    Action::Menu,
+    Action::Rematch,
+];
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::P1Up => "Player 1 up",
+            Action::P1Down => "Player 1 down",
+            Action::P2Up => "Player 2 up",
+            Action::P2Down => "Player 2 down",
+            Action::Pause => "Pause",
+            Action::Menu => "Menu",
+            Action::Rematch => "Rematch",
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Action::P1Up => 0,
+            Action::P1Down => 1,
+            Action::P2Up => 2,
+            Action::P2Down => 3,
+            Action::Pause => 4,
+            Action::Menu => 5,
+            Action::Rematch => 6,
+        }
+    }
+}
+
+// Keys a binding can be saved with, the position is what ends up on disk.
+// New ones go at the end, so saved bindings keep their meaning.
+// That's every key but `PowerOnTestOk`, which the keyboard sends after a reset
+const SAVED_KEYS: [KeyCode; 114] = [
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G,
+    KeyCode::H, KeyCode::I, KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N,
+    KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R, KeyCode::S, KeyCode::T, KeyCode::U,
+    KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::ArrowUp, KeyCode::ArrowDown, KeyCode::ArrowLeft, KeyCode::ArrowRight,
+    KeyCode::Spacebar, KeyCode::Enter, KeyCode::Escape, KeyCode::Tab, KeyCode::Backspace,
+    KeyCode::ShiftLeft, KeyCode::ShiftRight, KeyCode::ControlLeft, KeyCode::ControlRight,
+    KeyCode::AltLeft, KeyCode::AltRight,
+    KeyCode::Numpad0, KeyCode::Numpad1, KeyCode::Numpad2, KeyCode::Numpad3, KeyCode::Numpad4,
+    KeyCode::Numpad5, KeyCode::Numpad6, KeyCode::Numpad7, KeyCode::Numpad8,
+    KeyCode::Numpad9, KeyCode::NumpadEnter, KeyCode::NumpadLock, KeyCode::NumpadSlash,
+    KeyCode::NumpadStar, KeyCode::NumpadMinus, KeyCode::NumpadPeriod, KeyCode::NumpadPlus,
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+    KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    KeyCode::BackTick, KeyCode::Minus, KeyCode::Equals, KeyCode::BracketSquareLeft,
+    KeyCode::BracketSquareRight, KeyCode::BackSlash, KeyCode::HashTilde, KeyCode::SemiColon,
+    KeyCode::Quote, KeyCode::Comma, KeyCode::Fullstop, KeyCode::Slash,
+    KeyCode::Insert, KeyCode::Delete, KeyCode::Home, KeyCode::End, KeyCode::PageUp, KeyCode::PageDown,
+    KeyCode::CapsLock, KeyCode::ScrollLock, KeyCode::PrintScreen, KeyCode::PauseBreak,
+    KeyCode::WindowsLeft, KeyCode::WindowsRight, KeyCode::Menus,
+    KeyCode::PrevTrack, KeyCode::NextTrack, KeyCode::Mute, KeyCode::Calculator, KeyCode::Play,
+    KeyCode::Stop, KeyCode::VolumeDown, KeyCode::VolumeUp, KeyCode::WWWHome,
+];
+
+/// Why `Bindings::bind` didn't take a key
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BindError {
+    /// Another action already has the key
+    Taken(Action),
+    /// Not in `SAVED_KEYS`, saving it would reset all bindings
+    CantBeSaved,
+}
+
+/// Which key triggers which `Action`, every action has exactly one key
+/// and no key does two things
+#[derive(Clone, PartialEq, Debug)]
+pub struct Bindings {
+    keys: [KeyCode; ACTIONS.len()],
+}
+
+impl Bindings {
+    pub fn key(&self, action: Action) -> KeyCode {
+        self.keys[action.index()]
+    }
+
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        ACTIONS.iter().copied().find(|&action| self.key(action) == key)
+    }
+
+    /// Makes `key` trigger `action`. If `key` already belongs to
+    /// another action or can't be saved, nothing changes
+    pub fn bind(&mut self, action: Action, key: KeyCode) -> Result<(), BindError> {
+        if !SAVED_KEYS.contains(&key) {
+            return Err(BindError::CantBeSaved);
+        }
+        match self.action(key) {
+            Some(other) if other != action => Err(BindError::Taken(other)),
+            _ => {
+                self.keys[action.index()] = key;
+                Ok(())
+            }
+        }
+    }
+
+    /// One byte per action, `None` if one of the keys isn't in `SAVED_KEYS`
+    pub fn to_bytes(&self) -> Option<[u8; ACTIONS.len()]> {
+        let mut bytes = [0; ACTIONS.len()];
+        for (byte, key) in bytes.iter_mut().zip(self.keys.iter()) {
+            *byte = SAVED_KEYS.iter().position(|saved| saved == key)? as u8;
+        }
+        Some(bytes)
+    }
+
+    /// What `to_bytes` gave, `None` for unknown keys or a key used twice
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != ACTIONS.len() {
+            return None;
+        }
+        let mut keys = [KeyCode::Escape; ACTIONS.len()];
+        for (key, &byte) in keys.iter_mut().zip(bytes) {
+            *key = *SAVED_KEYS.get(byte as usize)?;
+        }
+
+        let bindings = Self { keys };
+        let unique = ACTIONS
+            .iter()
+            .all(|&action| bindings.action(bindings.key(action)) == Some(action));
+        if unique {
+            Some(bindings)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            keys: [
+                KeyCode::W,
+                KeyCode::S,
+                KeyCode::ArrowUp,
+                KeyCode::ArrowDown,
+                KeyCode::Spacebar,
+                KeyCode::Escape,
+                KeyCode::R,
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_can_be_moved_but_not_shared() {
+        let mut bindings = Bindings::default();
+
+        assert_eq!(bindings.bind(Action::P1Up, KeyCode::ArrowUp), Err(BindError::Taken(Action::P2Up)));
+        assert_eq!(bindings.key(Action::P1Up), KeyCode::W);
+
+        assert_eq!(bindings.bind(Action::P1Up, KeyCode::Q), Ok(()));
+        assert_eq!(bindings.action(KeyCode::Q), Some(Action::P1Up));
+        assert_eq!(bindings.action(KeyCode::W), None);
+        // Pressing the key an action already has is fine
+        assert_eq!(bindings.bind(Action::Pause, KeyCode::Spacebar), Ok(()));
+    }
+
+    #[test]
+    fn saved_bindings_come_back() {
+        let mut bindings = Bindings::default();
+        bindings.bind(Action::Menu, KeyCode::Numpad8).unwrap();
+
+        let bytes = bindings.to_bytes().unwrap();
+
+        assert_eq!(Bindings::from_bytes(&bytes), Some(bindings));
+        // The same key for two actions
+        assert_eq!(Bindings::from_bytes(&[0, 0, 1, 2, 3, 4, 5]), None);
+        assert_eq!(Bindings::from_bytes(&[0, 1, 2, 3, 4, 5, 200]), None);
+    }
+
+    #[test]
+    fn keys_past_the_first_saved_ones_come_back() {
+        let mut bindings = Bindings::default();
+        bindings.bind(Action::P1Up, KeyCode::Numpad9).unwrap();
+        bindings.bind(Action::P1Down, KeyCode::F12).unwrap();
+        bindings.bind(Action::Pause, KeyCode::Slash).unwrap();
+        bindings.bind(Action::Rematch, KeyCode::Home).unwrap();
+
+        let bytes = bindings.to_bytes().unwrap();
+
+        assert_eq!(Bindings::from_bytes(&bytes), Some(bindings));
+    }
+
+    #[test]
+    fn keys_that_cant_be_saved_are_refused() {
+        let mut bindings = Bindings::default();
+
+        assert_eq!(bindings.bind(Action::P1Up, KeyCode::PowerOnTestOk), Err(BindError::CantBeSaved));
+        assert_eq!(bindings.key(Action::P1Up), KeyCode::W);
+        assert!(bindings.to_bytes().is_some());
+    }
+}
@@ -0,0 +1,5 @@
+/// Time as seen by the game
+pub trait Clock {
+    /// Milliseconds since some fixed point in the past, wraps around
+    fn millis(&self) -> u32;
+}
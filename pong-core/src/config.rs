@@ -0,0 +1,467 @@
+use pc_keyboard::KeyCode;
+
+use crate::screen::{Color, ColorCode};
+
+use crate::bindings::{Action, BindError, Bindings};
+use crate::game_state::Player;
+
+pub struct GameConfig {
+    background: Color,
+    colors: (Color, Color),
+    controllers: (Controller, Controller),
+    difficulty: Difficulty,
+    target_score: u8,
+    win_by_two: bool,
+    ball_speed: u8,
+    paddle_height: u8,
+    paddle_speed: u8,
+    physics_rate: u32,
+    target_fps: u32,
+    muted: bool,
+    mouse_sensitivity: u8,
+    layout: Layout,
+    bindings: Bindings,
+}
+
+// Choices offered in the menu for `target_score`
+const TARGET_SCORES: [u8; 5] = [5, 7, 11, 15, 21];
+
+pub const MIN_BALL_SPEED: u8 = 1;
+pub const MAX_BALL_SPEED: u8 = 5;
+
+// Bars need a middle row, so only odd heights
+pub const MIN_PADDLE_HEIGHT: u8 = 3;
+pub const MAX_PADDLE_HEIGHT: u8 = 9;
+
+// In quarter cells per tick
+pub const MIN_PADDLE_SPEED: u8 = 1;
+pub const MAX_PADDLE_SPEED: u8 = 8;
+
+pub const MIN_MOUSE_SENSITIVITY: u8 = 1;
+pub const MAX_MOUSE_SENSITIVITY: u8 = 8;
+
+// Upper bound for `physics_rate` and `target_fps`
+pub const MAX_RATE: u32 = 1000;
+
+impl GameConfig {
+    pub fn set_color(&mut self, c: Color, p: Player) {
+        match p {
+            Player::Player1 => self.colors.0 = c,
+            Player::Player2 => self.colors.1 = c,
+        }
+    }
+
+    pub fn player_color(&self, p: Player) -> Color {
+        match p {
+            Player::Player1 => self.colors.0,
+            Player::Player2 => self.colors.1,
+        }
+    }
+
+    pub fn player_color_code(&self, p: Player) -> ColorCode {
+        let color = self.player_color(p);
+        ColorCode::new(color, self.background())
+    }
+
+    pub fn background(&self) -> Color {
+        self.background
+    }
+
+    // Only the lower 3 bits are a background color in text mode,
+    // the 4th one makes the foreground blink
+    pub fn set_background(&mut self, background: Color) {
+        self.background = ((background as u8) % 8).into();
+    }
+
+    pub fn next_background(&mut self) {
+        self.background = ((self.background as u8 + 1) % 8).into();
+    }
+
+    pub fn previous_background(&mut self) {
+        self.background = ((self.background as u8 + 7) % 8).into();
+    }
+
+    pub fn controller(&self, p: Player) -> Controller {
+        match p {
+            Player::Player1 => self.controllers.0,
+            Player::Player2 => self.controllers.1,
+        }
+    }
+
+    pub fn set_controller(&mut self, c: Controller, p: Player) {
+        match p {
+            Player::Player1 => self.controllers.0 = c,
+            Player::Player2 => self.controllers.1 = c,
+        }
+    }
+
+    pub fn toggle_controller(&mut self, p: Player) {
+        let next = match self.controller(p) {
+            Controller::Human => Controller::Cpu,
+            Controller::Cpu => Controller::Mouse,
+            Controller::Mouse => Controller::Human,
+        };
+        self.set_controller(next, p);
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn set_difficulty(&mut self, d: Difficulty) {
+        self.difficulty = d;
+    }
+
+    pub fn next_difficulty(&mut self) {
+        self.set_difficulty(self.difficulty().next());
+    }
+
+    pub fn previous_difficulty(&mut self) {
+        self.set_difficulty(self.difficulty().previous());
+    }
+
+    pub fn target_score(&self) -> u8 {
+        self.target_score
+    }
+
+    pub fn next_target_score(&mut self) {
+        let current = self.target_score();
+        let next = TARGET_SCORES
+            .iter()
+            .find(|&&score| score > current)
+            .unwrap_or(&TARGET_SCORES[0]);
+        self.target_score = *next;
+    }
+
+    /// Rounds up to the next choice of the menu
+    pub fn set_target_score(&mut self, score: u8) {
+        self.target_score = *TARGET_SCORES
+            .iter()
+            .find(|&&choice| choice >= score)
+            .unwrap_or(&TARGET_SCORES[TARGET_SCORES.len() - 1]);
+    }
+
+    pub fn previous_target_score(&mut self) {
+        let current = self.target_score();
+        let previous = TARGET_SCORES
+            .iter()
+            .rev()
+            .find(|&&score| score < current)
+            .unwrap_or(&TARGET_SCORES[TARGET_SCORES.len() - 1]);
+        self.target_score = *previous;
+    }
+
+    pub fn win_by_two(&self) -> bool {
+        self.win_by_two
+    }
+
+    pub fn set_win_by_two(&mut self, win_by_two: bool) {
+        self.win_by_two = win_by_two;
+    }
+
+    pub fn toggle_win_by_two(&mut self) {
+        self.win_by_two = !self.win_by_two;
+    }
+
+    /// Multiplier for the horizontal speed of a freshly served ball
+    pub fn ball_speed(&self) -> u8 {
+        self.ball_speed
+    }
+
+    pub fn set_ball_speed(&mut self, speed: u8) {
+        self.ball_speed = speed.clamp(MIN_BALL_SPEED, MAX_BALL_SPEED);
+    }
+
+    /// Height of a bar in rows, always odd
+    pub fn paddle_height(&self) -> u8 {
+        self.paddle_height
+    }
+
+    pub fn set_paddle_height(&mut self, height: u8) {
+        self.paddle_height = height.clamp(MIN_PADDLE_HEIGHT, MAX_PADDLE_HEIGHT) | 1;
+    }
+
+    /// Rows of a bar above (or below) its middle row
+    pub fn paddle_half_height(&self) -> u8 {
+        self.paddle_height() / 2
+    }
+
+    /// Quarter cells a bar moves per tick while its key is held
+    pub fn paddle_speed(&self) -> u8 {
+        self.paddle_speed
+    }
+
+    pub fn set_paddle_speed(&mut self, speed: u8) {
+        self.paddle_speed = speed.clamp(MIN_PADDLE_SPEED, MAX_PADDLE_SPEED);
+    }
+
+    /// Simulation steps per second, all speeds are per step
+    pub fn physics_rate(&self) -> u32 {
+        self.physics_rate
+    }
+
+    pub fn set_physics_rate(&mut self, hz: u32) {
+        self.physics_rate = hz.clamp(1, MAX_RATE);
+    }
+
+    /// Frames drawn per second
+    pub fn target_fps(&self) -> u32 {
+        self.target_fps
+    }
+
+    pub fn set_target_fps(&mut self, fps: u32) {
+        self.target_fps = fps.clamp(1, MAX_RATE);
+    }
+
+    /// No sound from the PC speaker
+    pub fn muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    pub fn toggle_muted(&mut self) {
+        self.muted = !self.muted;
+    }
+
+    /// Scales how far a `Controller::Mouse` bar moves for the same mouse movement
+    pub fn mouse_sensitivity(&self) -> u8 {
+        self.mouse_sensitivity
+    }
+
+    pub fn set_mouse_sensitivity(&mut self, sensitivity: u8) {
+        self.mouse_sensitivity = sensitivity.clamp(MIN_MOUSE_SENSITIVITY, MAX_MOUSE_SENSITIVITY);
+    }
+
+    /// Keyboard layout used to turn keys into characters
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.layout = layout;
+    }
+
+    pub fn next_layout(&mut self) {
+        self.set_layout(self.layout().next());
+    }
+
+    pub fn previous_layout(&mut self) {
+        self.set_layout(self.layout().previous());
+    }
+
+    /// Keys used in a match
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
+    pub fn set_bindings(&mut self, bindings: Bindings) {
+        self.bindings = bindings;
+    }
+
+    /// See `Bindings::bind`
+    pub fn bind(&mut self, action: Action, key: KeyCode) -> Result<(), BindError> {
+        self.bindings.bind(action, key)
+    }
+
+    /// The player who has won the match with `score`, if any
+    pub fn match_winner(&self, score: (u32, u32)) -> Option<Player> {
+        let target = self.target_score() as u32;
+        let lead = if self.win_by_two() { 2 } else { 1 };
+
+        if score.0 >= target && score.0 >= score.1 + lead {
+            Some(Player::Player1)
+        } else if score.1 >= target && score.1 >= score.0 + lead {
+            Some(Player::Player2)
+        } else {
+            None
+        }
+    }
+}
+
+/// Who steers a `PongBar`
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Controller {
+    Human,
+    Cpu,
+    /// Follows the vertical movement of the PS/2 mouse
+    Mouse,
+}
+
+impl Controller {
+    /// Padded to the same width so the menu can overwrite it in place
+    pub fn name(&self) -> &'static str {
+        match self {
+            Controller::Human => "Human",
+            Controller::Cpu => "CPU  ",
+            Controller::Mouse => "Mouse",
+        }
+    }
+}
+
+impl From<u8> for Controller {
+    fn from(n: u8) -> Self {
+        match n {
+            0 => Controller::Human,
+            1 => Controller::Cpu,
+            2 => Controller::Mouse,
+            _ => panic!("Invalid Controller"),
+        }
+    }
+}
+
+impl From<Controller> for u8 {
+    fn from(c: Controller) -> Self {
+        match c {
+            Controller::Human => 0,
+            Controller::Cpu => 1,
+            Controller::Mouse => 2,
+        }
+    }
+}
+
+/// Presets for the CPU player, see `ai::profile` for what they change
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Perfect,
+}
+
+impl Difficulty {
+    pub fn next(&self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Perfect,
+            Difficulty::Perfect => Difficulty::Easy,
+        }
+    }
+
+    pub fn previous(&self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Perfect,
+            Difficulty::Normal => Difficulty::Easy,
+            Difficulty::Hard => Difficulty::Normal,
+            Difficulty::Perfect => Difficulty::Hard,
+        }
+    }
+
+    /// Padded to the same width so the menu can overwrite it in place
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy   ",
+            Difficulty::Normal => "Normal ",
+            Difficulty::Hard => "Hard   ",
+            Difficulty::Perfect => "Perfect",
+        }
+    }
+}
+
+impl From<u8> for Difficulty {
+    fn from(n: u8) -> Self {
+        match n {
+            0 => Difficulty::Easy,
+            1 => Difficulty::Normal,
+            2 => Difficulty::Hard,
+            3 => Difficulty::Perfect,
+            _ => panic!("Invalid Difficulty"),
+        }
+    }
+}
+
+impl From<Difficulty> for u8 {
+    fn from(d: Difficulty) -> Self {
+        match d {
+            Difficulty::Easy => 0,
+            Difficulty::Normal => 1,
+            Difficulty::Hard => 2,
+            Difficulty::Perfect => 3,
+        }
+    }
+}
+
+/// Keyboard layouts the kernel can decode, named like the ones in `pc_keyboard::layouts`
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Layout {
+    Us104Key,
+    Uk105Key,
+    De105Key,
+    Azerty,
+    Dvorak104Key,
+}
+
+const LAYOUTS: [Layout; 5] = [
+    Layout::Us104Key,
+    Layout::Uk105Key,
+    Layout::De105Key,
+    Layout::Azerty,
+    Layout::Dvorak104Key,
+];
+
+impl Layout {
+    pub fn next(&self) -> Layout {
+        LAYOUTS[(u8::from(*self) as usize + 1) % LAYOUTS.len()]
+    }
+
+    pub fn previous(&self) -> Layout {
+        LAYOUTS[(u8::from(*self) as usize + LAYOUTS.len() - 1) % LAYOUTS.len()]
+    }
+
+    /// Padded to the same width so the menu can overwrite it in place
+    pub fn name(&self) -> &'static str {
+        match self {
+            Layout::Us104Key => "US    ",
+            Layout::Uk105Key => "UK    ",
+            Layout::De105Key => "German",
+            Layout::Azerty => "AZERTY",
+            Layout::Dvorak104Key => "Dvorak",
+        }
+    }
+}
+
+impl From<u8> for Layout {
+    fn from(n: u8) -> Self {
+        match LAYOUTS.get(n as usize) {
+            Some(&layout) => layout,
+            None => panic!("Invalid Layout"),
+        }
+    }
+}
+
+impl From<Layout> for u8 {
+    fn from(l: Layout) -> Self {
+        match l {
+            Layout::Us104Key => 0,
+            Layout::Uk105Key => 1,
+            Layout::De105Key => 2,
+            Layout::Azerty => 3,
+            Layout::Dvorak104Key => 4,
+        }
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            background: Color::Black,
+            colors: (Color::Yellow, Color::Yellow),
+            controllers: (Controller::Human, Controller::Human),
+            difficulty: Difficulty::Normal,
+            target_score: 11,
+            win_by_two: true,
+            ball_speed: 3,
+            paddle_height: 5,
+            paddle_speed: 2,
+            physics_rate: 18,
+            target_fps: 60,
+            muted: false,
+            mouse_sensitivity: 4,
+            layout: Layout::Uk105Key,
+            bindings: Bindings::default(),
+        }
+    }
+}
@@ -0,0 +1,524 @@
+use crate::screen::BUFFER_WIDTH;
+use crate::{
+    ai::CpuPlayer,
+    clock::Clock,
+    config::{Controller, GameConfig},
+    pongball::{bounce_off_walls, to_cell, BallPosition, Fixed, PongBall, Speed, HIGHEST_SPEED, LOWEST_SPEED},
+    pongbar::{Key, PongBar},
+    render::Renderer,
+    rng::Rng,
+};
+
+// What a hit does to the speed, depending on the zone of the bar, see `paddle_zone`
+const ZONE_0_DX: Fixed = Fixed::from_bits(55706); // 0.85
+const ZONE_0_DY: Fixed = Fixed::from_bits(58982); // 0.9
+const ZONE_1_DX: Fixed = Fixed::from_bits(75366); // 1.15
+const ZONE_2_DX: Fixed = Fixed::from_bits(72090); // 1.1
+const ZONE_2_DY: Fixed = Fixed::from_bits(88474); // 1.35
+
+pub struct GameState<R: Rng, C: Clock> {
+    pub player1: PongBar,
+    pub player2: PongBar,
+    pub ball: PongBall,
+    pub score: (u32, u32),
+    pub config: GameConfig,
+    pub cpu: (CpuPlayer, CpuPlayer),
+    pub high_scores: HighScores,
+    // Returns since the last serve
+    rally: u16,
+    rng: R,
+    clock: C,
+}
+
+/// Best results over all matches, the kernel keeps them on disk with the settings
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct HighScores {
+    /// Most times the ball was returned before a point
+    pub longest_rally: u16,
+    /// Matches a human or mouse player won against the CPU
+    pub wins_against_cpu: u16,
+}
+
+/// What happened during a `GameState::step`
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct StepEvents {
+    /// Zone of the bar the ball bounced off, see `paddle_zone`
+    pub paddle_hit: Option<u8>,
+    /// The ball bounced off the top or bottom wall
+    pub wall_bounce: bool,
+    /// Who got a point, the score has already been updated
+    pub scored: Option<Player>,
+    /// Set once the match is over, the ball stays where it is
+    pub winner: Option<Player>,
+}
+
+impl<R: Rng, C: Clock> GameState<R, C> {
+    pub fn new(config: GameConfig, mut rng: R, clock: C) -> Self {
+        Self {
+            player1: PongBar::new(Player::Player1),
+            player2: PongBar::new(Player::Player2),
+            ball: PongBall::new(config.ball_speed(), &mut rng),
+            score: (0, 0),
+            cpu: (
+                CpuPlayer::new(Player::Player1),
+                CpuPlayer::new(Player::Player2),
+            ),
+            config,
+            high_scores: HighScores::default(),
+            rally: 0,
+            rng,
+            clock,
+        }
+    }
+
+    pub fn step(&mut self) -> StepEvents {
+        self.move_players();
+        self.ball.move_ball();
+        self.eval_collisions()
+    }
+
+    pub fn render(&self, renderer: &mut impl Renderer) {
+        renderer.render_ball(&self.ball);
+        self.render_players(renderer);
+    }
+
+    fn move_players(&mut self) {
+        let half_height = self.config.paddle_half_height();
+        let speed = self.config.paddle_speed();
+        let now = self.clock.millis();
+
+        match self.config.controller(Player::Player1) {
+            Controller::Human => self.player1.move_player(half_height, speed),
+            Controller::Cpu => {
+                self.cpu.0.steer(&mut self.player1, &self.ball, &self.config, now, &mut self.rng)
+            }
+            // Moved in `mouse_moved`, only kept on screen here
+            Controller::Mouse => self.player1.shift(Key::None, half_height),
+        }
+        match self.config.controller(Player::Player2) {
+            Controller::Human => self.player2.move_player(half_height, speed),
+            Controller::Cpu => {
+                self.cpu.1.steer(&mut self.player2, &self.ball, &self.config, now, &mut self.rng)
+            }
+            Controller::Mouse => self.player2.shift(Key::None, half_height),
+        }
+    }
+
+    /// Moves every bar with `Controller::Mouse`, `dy` is in mouse counts with up being positive
+    pub fn mouse_moved(&mut self, dy: i16) {
+        let half_height = self.config.paddle_half_height();
+        let sensitivity = self.config.mouse_sensitivity();
+
+        if self.config.controller(Player::Player1) == Controller::Mouse {
+            self.player1.follow_mouse(dy, sensitivity, half_height);
+        }
+        if self.config.controller(Player::Player2) == Controller::Mouse {
+            self.player2.follow_mouse(dy, sensitivity, half_height);
+        }
+    }
+
+    // Bars are drawn in the menu as a preview of their color and height
+    pub fn show_menu(&mut self, renderer: &mut impl Renderer) {
+        let half_height = self.config.paddle_half_height();
+        let speed = self.config.paddle_speed();
+        self.player1.move_player(half_height, speed);
+        self.player2.move_player(half_height, speed);
+
+        self.render_players(renderer);
+    }
+
+    fn render_players(&self, renderer: &mut impl Renderer) {
+        let half_height = self.config.paddle_half_height();
+        renderer.render_player(&self.player1, half_height, self.config.player_color_code(Player::Player1));
+        renderer.render_player(&self.player2, half_height, self.config.player_color_code(Player::Player2));
+    }
+
+    /// Back to the menu, the config and the high scores stay
+    pub fn reset(&mut self) {
+        self.reset_match();
+    }
+
+    /// Starts over with `rng`, the same `rng` and the same inputs
+    /// play the same match
+    pub fn start_match(&mut self, rng: R) {
+        self.rng = rng;
+        self.reset_match();
+    }
+
+    pub fn rng(&self) -> &R {
+        &self.rng
+    }
+
+    /// Puts everything but the config back to the start of a match
+    pub fn reset_match(&mut self) {
+        self.reset_players();
+        self.cpu = (
+            CpuPlayer::new(Player::Player1),
+            CpuPlayer::new(Player::Player2),
+        );
+        reset_ball(&mut self.ball, self.config.ball_speed(), &mut self.rng);
+        self.score = (0, 0);
+        self.rally = 0;
+    }
+
+    pub fn reset_players(&mut self) {
+        reset_bars(&mut self.player1, &mut self.player2);
+    }
+
+    /// Looks at the path the ball took during the last `move_ball`.
+    /// A bar it crossed sends it back from the exact point of impact,
+    /// so even at `HIGHEST_SPEED` nothing is skipped.
+    pub fn eval_collisions(&mut self) -> StepEvents {
+        let mut events = StepEvents::default();
+        // Only disjoint fields are borrowed from here on,
+        // so the bars and the score can still be reset below
+        let ball = &mut self.ball;
+        let half_height = self.config.paddle_half_height();
+        //////////////////////////////////////////////////////
+        //                  Player collision                //
+        //////////////////////////////////////////////////////
+        // Only the bar the ball is moving towards can be hit
+        let (player, bar) = if ball.speed.dx.is_negative() {
+            (Player::Player1, &self.player1)
+        } else {
+            (Player::Player2, &self.player2)
+        };
+
+        let mut bounced = false;
+        if let Some(hit) = sweep_bar(ball, player) {
+            // CHECK HOW FAR THE BALL IS FROM THE MIDDLE FROM THE BAR
+            // THE MORE IT IS IN THE MIDDLE THE SLOWER IT GETS
+            let diff = (to_cell(hit.y) as i16 - bar.position.y as i16).abs();
+            if diff <= half_height as i16 {
+                // Walls on the way to the bar count as well
+                events.wall_bounce = hit.dy != ball.speed.dy;
+                ball.speed.dx = -ball.speed.dx;
+                ball.speed.dy = hit.dy;
+                let zone = paddle_zone(diff as u8, half_height);
+                apply_zone(&mut ball.speed, zone);
+                events.paddle_hit = Some(zone);
+                self.rally = self.rally.saturating_add(1);
+
+                // The rest of the step is spent going back
+                let rest = Fixed::from_num(1) - hit.t;
+                ball.position.x = paddle_face(player) + ball.speed.dx * rest;
+                ball.position.y = hit.y + ball.speed.dy * rest;
+                bounced = true;
+            }
+        }
+        //////////////////////////////////////////////////////
+        //                  Collision left/right wall       //
+        //////////////////////////////////////////////////////
+        if !bounced && (ball.position.x < LEFT_GOAL || ball.position.x >= RIGHT_GOAL) {
+            let scorer = match player {
+                Player::Player1 => Player::Player2,
+                Player::Player2 => Player::Player1,
+            };
+            reset_bars(&mut self.player1, &mut self.player2);
+            reset_ball(ball, self.config.ball_speed(), &mut self.rng);
+
+            match scorer {
+                Player::Player1 => self.score.0 += 1,
+                Player::Player2 => self.score.1 += 1,
+            }
+            events.scored = Some(scorer);
+            events.winner = self.config.match_winner(self.score);
+            self.record_high_scores(scorer, events.winner.is_some());
+            return events;
+        }
+
+        //////////////////////////////////////////////////////
+        //                  Collision top/bot wall          //
+        //////////////////////////////////////////////////////
+        let (y, dy) = bounce_off_walls(ball.position.y, ball.speed.dy);
+        events.wall_bounce |= dy != ball.speed.dy;
+        ball.position.y = y;
+        ball.speed.dy = dy;
+
+        events
+    }
+
+    fn record_high_scores(&mut self, scorer: Player, won: bool) {
+        let scores = &mut self.high_scores;
+        scores.longest_rally = scores.longest_rally.max(self.rally);
+        self.rally = 0;
+
+        let loser = match scorer {
+            Player::Player1 => Player::Player2,
+            Player::Player2 => Player::Player1,
+        };
+        let against_cpu = self.config.controller(scorer) != Controller::Cpu
+            && self.config.controller(loser) == Controller::Cpu;
+        if won && against_cpu {
+            scores.wins_against_cpu = scores.wins_against_cpu.saturating_add(1);
+        }
+    }
+}
+
+// Columns the ball has to pass for a point, the ones right next to the screen edges
+const LEFT_GOAL: Fixed = Fixed::from_bits(1 << 16);
+const RIGHT_GOAL: Fixed = Fixed::from_bits((BUFFER_WIDTH as i32 - 1) << 16);
+
+/// Where the ball crossed the face of a bar
+struct Hit {
+    // Part of the last step that was done when it happened, between 0 and 1
+    t: Fixed,
+    y: Fixed,
+    // Walls on the way there may have turned the ball around
+    dy: Fixed,
+}
+
+// `None` if the ball didn't pass the face of `player`'s bar during the last step,
+// whether the bar was actually there is up to the caller
+fn sweep_bar(ball: &PongBall, player: Player) -> Option<Hit> {
+    let face = paddle_face(player);
+    let from = ball.last_pos;
+    let to = ball.position;
+
+    let crossed = match player {
+        Player::Player1 => from.x >= face && to.x < face,
+        Player::Player2 => from.x < face && to.x >= face,
+    };
+    if !crossed {
+        return None;
+    }
+
+    let t = (face - from.x) / (to.x - from.x);
+    let (y, dy) = bounce_off_walls(from.y + (to.y - from.y) * t, ball.speed.dy);
+    Some(Hit { t, y, dy })
+}
+
+// What a hit in `zone` does to the speed, `dx` already points away from the bar
+fn apply_zone(speed: &mut Speed, zone: u8) {
+    match zone {
+        0 => {
+            speed.dx *= ZONE_0_DX;
+            if speed.dy.is_positive() {
+                speed.dy = (speed.dy * ZONE_0_DY).max(LOWEST_SPEED);
+            }
+        }
+        1 => speed.dx *= ZONE_1_DX,
+        2 => {
+            speed.dy = clamp_size(speed.dy * ZONE_2_DY, Fixed::from_num(0), HIGHEST_SPEED);
+            speed.dx *= ZONE_2_DX;
+        }
+        _ => panic!("`zone` greater than 2!: {}", zone),
+    }
+    speed.dx = clamp_size(speed.dx, LOWEST_SPEED, HIGHEST_SPEED);
+}
+
+// Keeps the sign of `value`, only its size is clamped
+fn clamp_size(value: Fixed, min: Fixed, max: Fixed) -> Fixed {
+    if value.is_negative() {
+        -(-value).clamp(min, max)
+    } else {
+        value.clamp(min, max)
+    }
+}
+
+// Both bars back to the middle of their column, keys released
+fn reset_bars(player1: &mut PongBar, player2: &mut PongBar) {
+    *player1 = PongBar::new(Player::Player1);
+    *player2 = PongBar::new(Player::Player2);
+}
+
+pub fn reset_ball(ball: &mut PongBall, start_speed: u8, rng: &mut impl Rng) {
+    let old_pos = ball.position;
+    ball.position = BallPosition::default();
+    ball.last_pos = old_pos;
+    ball.speed = Speed::random(start_speed, rng);
+}
+
+/// Column the ball bounces off in front of `player`'s bar
+pub fn paddle_face(player: Player) -> Fixed {
+    let bar = PongBar::new(player).position;
+    match player {
+        Player::Player1 => Fixed::from_num(bar.x + 1),
+        Player::Player2 => Fixed::from_num(bar.x),
+    }
+}
+
+// Maps the distance between the ball and the middle of a bar
+// onto the 3 zones (0, 1, 2) `eval_collisions` knows, whatever the height
+pub fn paddle_zone(diff: u8, half_height: u8) -> u8 {
+    (diff * 2 / half_height).min(2)
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Player {
+    Player1,
+    Player2,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Position {
+    pub x: u8,
+    pub y: u8,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // Always serves the same way
+    pub(crate) struct FixedRng(u8);
+
+    impl Rng for FixedRng {
+        fn next_u8(&mut self) -> u8 {
+            self.0
+        }
+    }
+
+    pub(crate) struct StoppedClock;
+
+    impl Clock for StoppedClock {
+        fn millis(&self) -> u32 {
+            0
+        }
+    }
+
+    pub(crate) fn game() -> GameState<FixedRng, StoppedClock> {
+        GameState::new(GameConfig::default(), FixedRng(0), StoppedClock)
+    }
+
+    // Starts the ball at (x, y) and makes one step of (dx, dy)
+    fn place_ball(state: &mut GameState<FixedRng, StoppedClock>, x: f64, y: f64, dx: f64, dy: f64) {
+        state.ball.position = BallPosition { x: Fixed::from_num(x), y: Fixed::from_num(y) };
+        state.ball.speed = Speed { dx: Fixed::from_num(dx), dy: Fixed::from_num(dy) };
+        state.ball.move_ball();
+    }
+
+    #[test]
+    fn middle_of_the_bar_reflects_and_slows_down() {
+        let mut state = game();
+        let row = state.player1.position.y as f64;
+        place_ball(&mut state, 2.5, row + 0.5, -1.0, 0.0);
+
+        let events = state.eval_collisions();
+
+        assert_eq!(events, StepEvents { paddle_hit: Some(0), ..StepEvents::default() });
+        assert_eq!(state.ball.speed.dx, Fixed::from_num(0.85));
+        // Hit the bar halfway through the step and spent the other half going back
+        assert_eq!(state.ball.position.x, Fixed::from_num(2) + Fixed::from_num(0.85) / Fixed::from_num(2));
+    }
+
+    #[test]
+    fn edge_of_the_bar_steepens_the_ball() {
+        let mut state = game();
+        let half_height = state.config.paddle_half_height();
+        let row = (state.player2.position.y + half_height) as f64;
+        place_ball(&mut state, 77.5, row + 0.5, 1.0, 0.5);
+
+        state.eval_collisions();
+
+        assert!(state.ball.speed.dx < Fixed::from_num(-1));
+        assert_eq!(state.ball.speed.dy, Fixed::from_num(0.5 * 1.35));
+    }
+
+    #[test]
+    fn missing_the_ball_scores_for_the_other_player() {
+        let mut state = game();
+        state.player1.position.y = 5;
+        place_ball(&mut state, 0.5, 20.5, -1.0, 0.0);
+
+        let events = state.eval_collisions();
+
+        assert_eq!(events.scored, Some(Player::Player2));
+        assert_eq!(events.winner, None);
+        assert_eq!(state.score, (0, 1));
+        // Everything is back in the middle for the next serve
+        assert_eq!(state.player1.position, PongBar::new(Player::Player1).position);
+        assert_eq!(state.ball.position.x, BallPosition::default().x);
+    }
+
+    #[test]
+    fn last_point_ends_the_match() {
+        let mut state = game();
+        state.score = (10, 9);
+        state.player2.position.y = 5;
+        place_ball(&mut state, 78.5, 20.5, 1.0, 0.0);
+
+        let events = state.step();
+
+        assert_eq!(events.winner, Some(Player::Player1));
+        assert_eq!(state.score, (11, 9));
+    }
+
+    #[test]
+    fn top_wall_bounces() {
+        let mut state = game();
+        place_ball(&mut state, 40.5, 1.5, 1.0, -1.0);
+
+        state.eval_collisions();
+
+        assert_eq!(state.ball.speed.dy, Fixed::from_num(1));
+        assert_eq!(state.ball.position.y, Fixed::from_num(1.5));
+    }
+
+    #[test]
+    fn ball_past_the_top_row_comes_back() {
+        let mut state = game();
+        // Ends up above the screen, row 0 minus one used to wrap around
+        place_ball(&mut state, 40.5, 1.25, 1.0, -2.0);
+
+        assert_eq!(state.eval_collisions(), StepEvents { wall_bounce: true, ..StepEvents::default() });
+        assert_eq!(state.ball.speed.dy, Fixed::from_num(2));
+        assert_eq!(state.ball.position.y, Fixed::from_num(2.75));
+    }
+
+    #[test]
+    fn same_seed_same_match() {
+        use crate::rng::Pcg32;
+
+        let mut first = GameState::new(GameConfig::default(), Pcg32::new(1), StoppedClock);
+        let mut second = GameState::new(GameConfig::default(), Pcg32::new(2), StoppedClock);
+        first.start_match(Pcg32::new(7));
+        second.start_match(Pcg32::new(first.rng().seed()));
+
+        // Nobody touches the bars, so there are plenty of serves
+        (0..2000).for_each(|_| {
+            first.step();
+            second.step();
+        });
+
+        assert!(first.score.0 + first.score.1 > 0);
+        assert_eq!(first.score, second.score);
+        assert_eq!(first.ball.position.x, second.ball.position.x);
+        assert_eq!(first.ball.position.y, second.ball.position.y);
+    }
+
+    #[test]
+    fn mouse_only_moves_mouse_bars() {
+        let mut state = game();
+        state.config.set_controller(Controller::Mouse, Player::Player2);
+        let start = state.player2.position.y;
+
+        // 8 counts per row at the default sensitivity, half a row is kept for later
+        state.mouse_moved(-20);
+        assert_eq!(state.player2.position.y, start + 2);
+        state.mouse_moved(-4);
+        assert_eq!(state.player2.position.y, start + 3);
+        state.mouse_moved(16);
+        assert_eq!(state.player2.position.y, start + 1);
+
+        assert_eq!(state.player1.position, PongBar::new(Player::Player1).position);
+    }
+
+    #[test]
+    fn rallies_and_wins_against_the_cpu_are_high_scores() {
+        let mut state = game();
+        state.config.set_controller(Controller::Cpu, Player::Player2);
+        state.score = (10, 0);
+        let row = state.player1.position.y as f64;
+        place_ball(&mut state, 2.5, row + 0.5, -1.0, 0.0);
+        state.eval_collisions();
+        place_ball(&mut state, 2.5, row + 0.5, -1.0, 0.0);
+        state.eval_collisions();
+
+        state.player2.position.y = 5;
+        place_ball(&mut state, 78.5, 20.5, 1.0, 0.0);
+        state.eval_collisions();
+
+        assert_eq!(state.high_scores, HighScores { longest_rally: 2, wins_against_cpu: 1 });
+    }
+}
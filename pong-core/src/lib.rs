@@ -0,0 +1,24 @@
+//! Everything about a game of pong that doesn't need the hardware.
+//! Drawing, time and randomness are provided by the kernel
+//! through the `Surface`, `Clock` and `Rng` traits.
+#![cfg_attr(not(test), no_std)]
+
+pub mod ai;
+pub mod bindings;
+pub mod clock;
+pub mod config;
+pub mod game_state;
+pub mod pongball;
+pub mod pongbar;
+pub mod record;
+pub mod render;
+pub mod rng;
+pub mod screen;
+pub mod surface;
+
+pub use clock::Clock;
+pub use config::GameConfig;
+pub use game_state::{GameState, HighScores, Player, Position, StepEvents};
+pub use render::{Renderer, ScreenRenderer};
+pub use rng::{Pcg32, Rng};
+pub use surface::{Frame, Surface};
@@ -0,0 +1,167 @@
+use fixed::types::I16F16;
+
+use crate::rng::Rng;
+use crate::screen::{BUFFER_HEIGHT, BUFFER_WIDTH};
+
+/// Positions in cells and speeds in cells per step.
+/// Fixed point, so every build computes exactly the same game
+/// and nothing goes through the soft-float routines
+pub type Fixed = I16F16;
+
+// In cells per step, see `GameConfig::physics_rate`.
+// The ball used to move on every third timer tick, about 6 steps a second.
+// Physics runs at 18 steps a second now, so speeds are a third of what they were.
+pub static LOWEST_SPEED: Fixed = Fixed::from_bits(10923); // 0.5 / 3
+// Only a cap, `eval_collisions` follows the whole path of a fast ball
+pub static HIGHEST_SPEED: Fixed = Fixed::from_bits(10 << 16);
+
+// Rows the ball bounces between
+// row 0 is reserved for the score
+pub const FIELD_TOP: Fixed = Fixed::from_bits(1 << 16);
+pub const FIELD_BOTTOM: Fixed = Fixed::from_bits((BUFFER_HEIGHT as i32 - 1) << 16);
+
+const PI: Fixed = Fixed::from_bits(205887);
+const HALF_PI: Fixed = Fixed::from_bits(102944);
+
+pub struct PongBall {
+    pub last_pos: BallPosition,
+    pub position: BallPosition,
+    pub speed: Speed,
+}
+
+impl PongBall {
+    pub fn new(start_speed: u8, rng: &mut impl Rng) -> Self {
+        Self {
+            last_pos: BallPosition {
+                x: half(BUFFER_WIDTH) - Fixed::from_num(1),
+                y: half(BUFFER_HEIGHT) - Fixed::from_num(1),
+            },
+            position: BallPosition {
+                x: half(BUFFER_WIDTH),
+                y: half(BUFFER_HEIGHT),
+            },
+            speed: Speed::random(start_speed, rng),
+        }
+    }
+
+    pub fn move_ball(&mut self) {
+        self.last_pos = self.position;
+
+        self.position.x += self.speed.dx;
+        self.position.y += self.speed.dy;
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct BallPosition {
+    pub x: Fixed,
+    pub y: Fixed,
+}
+
+impl Default for BallPosition {
+    fn default() -> Self {
+        Self {
+            x: half(BUFFER_WIDTH),
+            y: half(BUFFER_HEIGHT),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Speed {
+    pub dx: Fixed,
+    pub dy: Fixed,
+}
+
+impl Speed {
+    /// Serve in a "random" direction, `start_speed` scales the horizontal part
+    pub fn random(start_speed: u8, rng: &mut impl Rng) -> Self {
+        get_random_start_speeds(start_speed, rng.next_u8())
+    }
+}
+
+/// The cell `value` falls into, what `floorf(value) as u8` used to give.
+/// Anything left of or above the screen ends up in cell 0.
+pub fn to_cell(value: Fixed) -> u8 {
+    value.floor().to_num::<i32>().clamp(0, u8::MAX as i32) as u8
+}
+
+/// Mirrors `y` back into the field for every wall it went past,
+/// `dy` is turned around the same number of times
+pub fn bounce_off_walls(y: Fixed, dy: Fixed) -> (Fixed, Fixed) {
+    let span = FIELD_BOTTOM - FIELD_TOP;
+    let period = span + span;
+
+    // Same scale on both sides, so the remainder of the raw bits is exact
+    let offset = Fixed::from_bits((y - FIELD_TOP).to_bits().rem_euclid(period.to_bits()));
+    if offset > span {
+        (FIELD_TOP + period - offset, -dy)
+    } else {
+        (FIELD_TOP + offset, dy)
+    }
+}
+
+// Half of a screen dimension, e.g. 12.5 for the 25 rows
+fn half(cells: usize) -> Fixed {
+    Fixed::from_bits((cells as i32) << 15)
+}
+
+fn get_random_start_speeds(start_speed: u8, random_u8: u8) -> Speed {
+    const NEAR_VERTICAL: Fixed = Fixed::from_bits(19661); // 0.3
+    const PUSH: Fixed = Fixed::from_bits(26214); // 0.4
+    const DY_SCALE: Fixed = Fixed::from_bits(17695); // 0.27, about 0.8 / 3, see `LOWEST_SPEED`
+
+    // "Random" angle between 0 and PI.
+    // Used to be a number between -1 and 1 times PI, but only its size ever mattered
+    let spread = (random_u8 as i32 * 2 - u8::MAX as i32).abs();
+    let angle = Fixed::from_num(spread) * PI / Fixed::from_num(u8::MAX);
+
+    let mut x = cos(angle);
+    let y = angle;
+    if !x.is_negative() && x < NEAR_VERTICAL {
+        x += PUSH;
+    } else if !x.is_positive() && x > -NEAR_VERTICAL {
+        x -= PUSH;
+    }
+
+    // Speed parameters
+    // `start_speed` is set in the menu, 3 is about a cell per step
+    Speed {
+        dx: Fixed::from_num(start_speed) / Fixed::from_num(3) * x,
+        dy: DY_SCALE * y,
+    }
+}
+
+// Bhaskara's approximation for 0..=PI, off by less than 0.002.
+// Plenty to pick a direction, and the same on every build
+fn cos(angle: Fixed) -> Fixed {
+    if angle > HALF_PI {
+        return -cos(PI - angle);
+    }
+    let pi_squared = PI * PI;
+    let angle_squared = angle * angle;
+    (pi_squared - Fixed::from_num(4) * angle_squared) / (pi_squared + angle_squared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cos_is_close_enough() {
+        (0..=100).for_each(|i| {
+            let angle = PI * Fixed::from_num(i) / Fixed::from_num(100);
+            let error = cos(angle).to_num::<f64>() - angle.to_num::<f64>().cos();
+            assert!(error.abs() < 0.002, "cos({}) is off by {}", angle, error);
+        });
+    }
+
+    #[test]
+    fn no_serve_goes_straight_up_or_down() {
+        (0..=u8::MAX).for_each(|random_u8| {
+            let speed = get_random_start_speeds(3, random_u8);
+            assert!(speed.dx.abs() >= Fixed::from_num(0.3), "{:?}", speed);
+            assert!(!speed.dy.is_negative(), "{:?}", speed);
+        });
+    }
+}
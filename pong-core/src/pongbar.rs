@@ -0,0 +1,126 @@
+use crate::game_state::{Player, Position};
+use crate::screen::{BUFFER_HEIGHT, BUFFER_WIDTH};
+
+// Mouse counts per row at a sensitivity of 1,
+// the default of 4 makes it 8 counts or about 2 mm
+const MOUSE_COUNTS_PER_ROW: i16 = 32;
+
+pub struct PongBar {
+    pub position: Position,
+    // Set while the key is held down, cleared the moment it is released
+    up_held: bool,
+    down_held: bool,
+    // Quarter cells moved that didn't add up to a full cell yet
+    progress: u8,
+    // Same for the mouse, in counts times sensitivity
+    mouse_progress: i16,
+}
+
+impl PongBar {
+    pub fn new(player: Player) -> Self {
+        match player {
+            Player::Player1 => PongBar {
+                position: Position {
+                    x: 1,
+                    y: BUFFER_HEIGHT as u8 / 2,
+                },
+                up_held: false,
+                down_held: false,
+                progress: 0,
+                mouse_progress: 0,
+            },
+            Player::Player2 => PongBar {
+                position: Position {
+                    x: BUFFER_WIDTH as u8 - 2,
+                    y: BUFFER_HEIGHT as u8 / 2,
+                },
+                up_held: false,
+                down_held: false,
+                progress: 0,
+                mouse_progress: 0,
+            },
+        }
+    }
+
+    pub fn press(&mut self, key: Key) {
+        match key {
+            Key::Up => self.up_held = true,
+            Key::Down => self.down_held = true,
+            Key::None => {}
+        }
+    }
+
+    pub fn release(&mut self, key: Key) {
+        match key {
+            Key::Up => self.up_held = false,
+            Key::Down => self.down_held = false,
+            Key::None => {}
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        self.release(Key::Up);
+        self.release(Key::Down);
+        self.progress = 0;
+    }
+
+    /// Direction the held keys point to, holding both cancels out
+    pub fn held_direction(&self) -> Key {
+        match (self.up_held, self.down_held) {
+            (true, false) => Key::Up,
+            (false, true) => Key::Down,
+            _ => Key::None,
+        }
+    }
+
+    /// Moves the bar `speed` quarter cells in the held direction
+    pub fn move_player(&mut self, half_height: u8, speed: u8) {
+        let key = self.held_direction();
+        if let Key::None = key {
+            self.progress = 0;
+            // Still keeps the bar on screen in case its height changed
+            self.shift(Key::None, half_height);
+            return;
+        }
+
+        let progress = self.progress + speed;
+        self.progress = progress % 4;
+
+        (0..progress / 4).for_each(|_| self.shift(key, half_height));
+    }
+
+    /// Moves the bar along with the mouse, `dy` is in mouse counts, up is positive
+    pub fn follow_mouse(&mut self, dy: i16, sensitivity: u8, half_height: u8) {
+        // Rows go down the screen
+        let progress = self.mouse_progress - dy * sensitivity as i16;
+        self.mouse_progress = progress % MOUSE_COUNTS_PER_ROW;
+
+        let rows = progress / MOUSE_COUNTS_PER_ROW;
+        let key = if rows < 0 { Key::Up } else { Key::Down };
+        (0..rows.abs()).for_each(|_| self.shift(key, half_height));
+    }
+
+    /// Moves the bar by one cell in the direction of `key`, staying on screen
+    pub fn shift(&mut self, key: Key, half_height: u8) {
+        let pos = &mut self.position;
+        // Keep the whole bar between the score row and the last row
+        let highest = half_height + 1;
+        let lowest = BUFFER_HEIGHT as u8 - 2 - half_height;
+
+        match key {
+            Key::Up if pos.y > highest => pos.y -= 1,
+            Key::Down if pos.y < lowest => pos.y += 1,
+            _ => {}
+        }
+
+        // The height may have changed in the menu since the last move
+        pos.y = pos.y.max(highest).min(lowest);
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Key {
+    Up,
+    Down,
+    None,
+}
@@ -0,0 +1,274 @@
+//! The settings of a `GameConfig` and the `HighScores` as bytes,
+//! so the kernel can keep them somewhere that survives a reboot.
+//!
+//! ```text
+//! 0..4    "PONG"
+//! 4       RECORD_VERSION
+//! 5..26   settings, see `encode_settings`
+//! 26..30  longest rally and wins against the CPU, little endian
+//! 30..32  `checksum` of everything before it, little endian
+//! ```
+//!
+//! There's also a blob with only a few of the settings,
+//! small enough for the spare bytes of the CMOS RAM:
+//!
+//! ```text
+//! 0       'P'
+//! 1       BLOB_VERSION
+//! 2..7    player colors, target score, difficulty, keyboard layout
+//! 7..9    `checksum` of everything before it, little endian
+//! ```
+use crate::bindings::{Bindings, ACTIONS};
+use crate::config::{Controller, Difficulty, GameConfig, Layout};
+use crate::game_state::{HighScores, Player};
+
+const MAGIC: [u8; 4] = *b"PONG";
+
+/// Goes up whenever the layout above changes, older records are ignored then
+pub const RECORD_VERSION: u8 = 1;
+
+const SETTINGS_LEN: usize = 14 + ACTIONS.len();
+const SETTINGS_START: usize = MAGIC.len() + 1;
+const HIGH_SCORES_START: usize = SETTINGS_START + SETTINGS_LEN;
+const CHECKSUM_START: usize = HIGH_SCORES_START + 4;
+
+pub const RECORD_LEN: usize = CHECKSUM_START + 2;
+
+// Written for bindings `Bindings::to_bytes` can't save, see `SAVED_KEYS`
+const NO_KEY: u8 = 0xff;
+
+const BLOB_MAGIC: u8 = b'P';
+
+/// Like `RECORD_VERSION`, for the blob
+pub const BLOB_VERSION: u8 = 1;
+
+pub const BLOB_LEN: usize = 9;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RecordError {
+    /// Nothing was ever saved there
+    NoRecord,
+    /// Saved by a version of the game that stored something else
+    UnknownVersion(u8),
+    /// The checksum doesn't match or a setting is out of range
+    Corrupt,
+}
+
+pub fn encode(config: &GameConfig, high_scores: &HighScores) -> [u8; RECORD_LEN] {
+    let mut record = [0; RECORD_LEN];
+    record[..MAGIC.len()].copy_from_slice(&MAGIC);
+    record[MAGIC.len()] = RECORD_VERSION;
+    encode_settings(config, &mut record[SETTINGS_START..HIGH_SCORES_START]);
+    record[HIGH_SCORES_START..HIGH_SCORES_START + 2].copy_from_slice(&high_scores.longest_rally.to_le_bytes());
+    record[HIGH_SCORES_START + 2..CHECKSUM_START].copy_from_slice(&high_scores.wins_against_cpu.to_le_bytes());
+
+    let checksum = checksum(&record[..CHECKSUM_START]);
+    record[CHECKSUM_START..].copy_from_slice(&checksum.to_le_bytes());
+    record
+}
+
+/// What `encode` was given. `bytes` may be longer than `RECORD_LEN`, e.g. a whole sector.
+/// Bindings that couldn't be saved come back as the default ones.
+pub fn decode(bytes: &[u8]) -> Result<(GameConfig, HighScores), RecordError> {
+    if bytes.len() < RECORD_LEN || bytes[..MAGIC.len()] != MAGIC {
+        return Err(RecordError::NoRecord);
+    }
+    if bytes[MAGIC.len()] != RECORD_VERSION {
+        return Err(RecordError::UnknownVersion(bytes[MAGIC.len()]));
+    }
+    let saved = u16::from_le_bytes([bytes[CHECKSUM_START], bytes[CHECKSUM_START + 1]]);
+    if saved != checksum(&bytes[..CHECKSUM_START]) {
+        return Err(RecordError::Corrupt);
+    }
+
+    let config = decode_settings(&bytes[SETTINGS_START..HIGH_SCORES_START]).ok_or(RecordError::Corrupt)?;
+    let high_scores = HighScores {
+        longest_rally: u16::from_le_bytes([bytes[HIGH_SCORES_START], bytes[HIGH_SCORES_START + 1]]),
+        wins_against_cpu: u16::from_le_bytes([bytes[HIGH_SCORES_START + 2], bytes[HIGH_SCORES_START + 3]]),
+    };
+    Ok((config, high_scores))
+}
+
+pub fn encode_blob(config: &GameConfig) -> [u8; BLOB_LEN] {
+    let mut blob = [0; BLOB_LEN];
+    blob[0] = BLOB_MAGIC;
+    blob[1] = BLOB_VERSION;
+    blob[2] = config.player_color(Player::Player1) as u8;
+    blob[3] = config.player_color(Player::Player2) as u8;
+    blob[4] = config.target_score();
+    blob[5] = config.difficulty().into();
+    blob[6] = config.layout().into();
+
+    let checksum = checksum(&blob[..BLOB_LEN - 2]);
+    blob[BLOB_LEN - 2..].copy_from_slice(&checksum.to_le_bytes());
+    blob
+}
+
+/// Sets what `encode_blob` saved on `config`, nothing changes if the blob is no good
+pub fn decode_blob(blob: &[u8; BLOB_LEN], config: &mut GameConfig) -> Result<(), RecordError> {
+    if blob[0] != BLOB_MAGIC {
+        return Err(RecordError::NoRecord);
+    }
+    if blob[1] != BLOB_VERSION {
+        return Err(RecordError::UnknownVersion(blob[1]));
+    }
+    let saved = u16::from_le_bytes([blob[BLOB_LEN - 2], blob[BLOB_LEN - 1]]);
+    if saved != checksum(&blob[..BLOB_LEN - 2]) {
+        return Err(RecordError::Corrupt);
+    }
+    if blob[2] >= 16 || blob[3] >= 16 || blob[5] >= 4 || blob[6] >= 5 {
+        return Err(RecordError::Corrupt);
+    }
+
+    config.set_color(blob[2].into(), Player::Player1);
+    config.set_color(blob[3].into(), Player::Player2);
+    config.set_target_score(blob[4]);
+    config.set_difficulty(Difficulty::from(blob[5]));
+    config.set_layout(Layout::from(blob[6]));
+    Ok(())
+}
+
+/// Fletcher-16, catches flipped bits as well as swapped bytes
+pub fn checksum(bytes: &[u8]) -> u16 {
+    let (low, high) = bytes.iter().fold((0u16, 0u16), |(low, high), &byte| {
+        let low = (low + byte as u16) % 255;
+        (low, (high + low) % 255)
+    });
+    high << 8 | low
+}
+
+// Everything the menu can change, `physics_rate` and `target_fps` are left out
+fn encode_settings(config: &GameConfig, out: &mut [u8]) {
+    out[0] = config.background() as u8;
+    out[1] = config.player_color(Player::Player1) as u8;
+    out[2] = config.player_color(Player::Player2) as u8;
+    out[3] = config.controller(Player::Player1).into();
+    out[4] = config.controller(Player::Player2).into();
+    out[5] = config.difficulty().into();
+    out[6] = config.target_score();
+    out[7] = config.win_by_two() as u8;
+    out[8] = config.ball_speed();
+    out[9] = config.paddle_height();
+    out[10] = config.paddle_speed();
+    out[11] = config.muted() as u8;
+    out[12] = config.mouse_sensitivity();
+    out[13] = config.layout().into();
+    let bindings = config.bindings().to_bytes().unwrap_or([NO_KEY; ACTIONS.len()]);
+    out[14..].copy_from_slice(&bindings);
+}
+
+// The `From<u8>` conversions panic on values they don't know, so those are checked first.
+// Numbers are clamped by their setters anyway.
+fn decode_settings(bytes: &[u8]) -> Option<GameConfig> {
+    let mut config = GameConfig::default();
+
+    let in_range = bytes[0] < 8
+        && bytes[1] < 16
+        && bytes[2] < 16
+        && bytes[3] < 3
+        && bytes[4] < 3
+        && bytes[5] < 4
+        && bytes[7] < 2
+        && bytes[11] < 2
+        && bytes[13] < 5;
+    if !in_range {
+        return None;
+    }
+
+    config.set_background(bytes[0].into());
+    config.set_color(bytes[1].into(), Player::Player1);
+    config.set_color(bytes[2].into(), Player::Player2);
+    config.set_controller(Controller::from(bytes[3]), Player::Player1);
+    config.set_controller(Controller::from(bytes[4]), Player::Player2);
+    config.set_difficulty(Difficulty::from(bytes[5]));
+    config.set_target_score(bytes[6]);
+    config.set_win_by_two(bytes[7] == 1);
+    config.set_ball_speed(bytes[8]);
+    config.set_paddle_height(bytes[9]);
+    config.set_paddle_speed(bytes[10]);
+    config.set_muted(bytes[11] == 1);
+    config.set_mouse_sensitivity(bytes[12]);
+    config.set_layout(Layout::from(bytes[13]));
+    config.set_bindings(Bindings::from_bytes(&bytes[14..]).unwrap_or_default());
+    Some(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::screen::Color;
+
+    fn changed_config() -> GameConfig {
+        let mut config = GameConfig::default();
+        config.set_color(Color::LightRed, Player::Player1);
+        config.set_controller(Controller::Cpu, Player::Player2);
+        config.next_difficulty();
+        config.next_target_score();
+        config.toggle_muted();
+        config.next_layout();
+        config
+    }
+
+    #[test]
+    fn settings_and_high_scores_come_back() {
+        let high_scores = HighScores { longest_rally: 300, wins_against_cpu: 2 };
+        let mut sector = [0; 512];
+        sector[..RECORD_LEN].copy_from_slice(&encode(&changed_config(), &high_scores));
+
+        let (config, loaded) = decode(&sector).unwrap();
+
+        assert_eq!(loaded, high_scores);
+        assert_eq!(encode(&config, &loaded), encode(&changed_config(), &high_scores));
+        assert_eq!(config.player_color(Player::Player1), Color::LightRed);
+    }
+
+    #[test]
+    fn broken_records_are_refused() {
+        let mut record = encode(&changed_config(), &HighScores::default());
+
+        assert_eq!(decode(&[0; 512]).err(), Some(RecordError::NoRecord));
+        assert_eq!(decode(&record[..RECORD_LEN - 1]).err(), Some(RecordError::NoRecord));
+
+        record[SETTINGS_START + 3] ^= 0x10;
+        assert_eq!(decode(&record).err(), Some(RecordError::Corrupt));
+
+        record[MAGIC.len()] = RECORD_VERSION + 1;
+        assert_eq!(decode(&record).err(), Some(RecordError::UnknownVersion(RECORD_VERSION + 1)));
+    }
+
+    #[test]
+    fn values_out_of_range_are_corrupt_even_with_a_good_checksum() {
+        let mut record = encode(&GameConfig::default(), &HighScores::default());
+        record[SETTINGS_START + 5] = 9;
+        let checksum = checksum(&record[..CHECKSUM_START]);
+        record[CHECKSUM_START..].copy_from_slice(&checksum.to_le_bytes());
+
+        assert_eq!(decode(&record).err(), Some(RecordError::Corrupt));
+    }
+
+    #[test]
+    fn blob_only_brings_back_its_settings() {
+        let blob = encode_blob(&changed_config());
+        let mut config = GameConfig::default();
+
+        decode_blob(&blob, &mut config).unwrap();
+
+        assert_eq!(encode_blob(&config), blob);
+        assert_eq!(config.player_color(Player::Player1), Color::LightRed);
+        // Not part of the blob
+        assert!(!config.muted());
+    }
+
+    #[test]
+    fn broken_blobs_change_nothing() {
+        let mut config = changed_config();
+        let before = encode(&config, &HighScores::default());
+
+        assert_eq!(decode_blob(&[0; BLOB_LEN], &mut config), Err(RecordError::NoRecord));
+        let mut blob = encode_blob(&GameConfig::default());
+        blob[4] ^= 1;
+        assert_eq!(decode_blob(&blob, &mut config), Err(RecordError::Corrupt));
+
+        assert_eq!(encode(&config, &HighScores::default()), before);
+    }
+}
@@ -0,0 +1,244 @@
+use crate::pongball::{to_cell, PongBall};
+use crate::pongbar::PongBar;
+use crate::screen::{ColorCode, ScreenChar, BUFFER_HEIGHT, BUFFER_WIDTH};
+use crate::surface::Surface;
+
+/// Something that can show the game, see `GameState::render`
+pub trait Renderer {
+    fn render_ball(&mut self, ball: &PongBall);
+    fn render_player(&mut self, bar: &PongBar, half_height: u8, color_code: ColorCode);
+    fn render_score(&mut self, score: (u32, u32));
+}
+
+/// Draws the game onto any `Surface`
+pub struct ScreenRenderer<S: Surface> {
+    surface: S,
+    // Cell (row, col) the ball was last drawn at, several steps may
+    // have passed since then so `last_pos` can't be used to erase it
+    rendered_ball: Option<(usize, usize)>,
+}
+
+impl<S: Surface> ScreenRenderer<S> {
+    pub fn new(surface: S) -> Self {
+        Self {
+            surface,
+            rendered_ball: None,
+        }
+    }
+
+    pub fn surface(&self) -> &S {
+        &self.surface
+    }
+
+    /// For everything that isn't part of the game itself, like the menu
+    pub fn surface_mut(&mut self) -> &mut S {
+        &mut self.surface
+    }
+}
+
+impl<S: Surface> Renderer for ScreenRenderer<S> {
+    fn render_ball(&mut self, ball: &PongBall) {
+        render_ball(&mut self.surface, ball, &mut self.rendered_ball);
+    }
+
+    fn render_player(&mut self, bar: &PongBar, half_height: u8, color_code: ColorCode) {
+        render_player(&mut self.surface, bar, half_height, &color_code);
+    }
+
+    fn render_score(&mut self, score: (u32, u32)) {
+        render_score(&mut self.surface, score);
+    }
+}
+
+pub fn render_score(surface: &mut impl Surface, score: (u32, u32)) {
+    let middle = BUFFER_WIDTH / 2;
+    let (mut score_p1, mut score_p2) = score;
+
+    // log10(2**32) + 1
+    let max_digits_u32 = 10;
+
+    let digits_in_p1 = libm::floorf(libm::log10f(score_p1 as f32)) as u32 + 1;
+
+    let digits_in_p2 = libm::floorf(libm::log10f(score_p2 as f32)) as u32 + 1;
+
+    (0..max_digits_u32).for_each(|i| {
+        surface.write_byte_at_pos(0, middle - i as usize + 1, b' ');
+    });
+
+    (0..digits_in_p1).map(|i| {
+        let s = (score_p1 % 10) as u8;
+        score_p1 /= 10;
+        (i, s)
+    }).for_each(|(i,  s)| {
+        surface.write_byte_at_pos(0, middle -i as usize - 1, 0x30 + s);
+    });
+
+
+
+    (0..max_digits_u32).for_each(|i| {
+        surface.write_byte_at_pos(0, middle + i as usize + 1, b' ');
+    });
+
+    (0..digits_in_p2).map(|i| {
+        let s = (score_p2 % 10) as u8;
+        score_p2 /= 10;
+        (i, s)
+    }).rev().for_each(|(i, s)| {
+        surface.write_byte_at_pos(0, middle + i as usize + 1, 0x30 + s)
+    });
+
+    surface.write_byte_at_pos(0, middle, b':');
+}
+
+fn render_player(surface: &mut impl Surface, bar: &PongBar, half_height: u8, color_code: &ColorCode) {
+    let height = BUFFER_HEIGHT;
+    let coords = bar.position;
+    (0..height).for_each(|row| {
+        surface.write_byte_at_pos(row, coords.x as usize, b' ');
+    });
+
+    (coords.y - half_height..=coords.y + half_height).for_each(|row| {
+        surface.write_screen_char_at_pos(row as usize, coords.x as usize, ScreenChar { ascii_character: 0xfe, color_code: *color_code});
+    })
+}
+
+fn render_ball(surface: &mut impl Surface, ball: &PongBall, rendered_at: &mut Option<(usize, usize)>) {
+    let cell = (to_cell(ball.position.y) as usize, to_cell(ball.position.x) as usize);
+
+    if let Some((row, col)) = rendered_at.replace(cell) {
+        surface.write_byte_at_pos(row, col, b' ');
+    }
+
+    surface.write_byte_at_pos(cell.0, cell.1, 0x0040)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::tests::game;
+    use crate::game_state::Player;
+    use crate::pongball::Fixed;
+    use crate::screen::Color;
+    use crate::surface::Frame;
+
+    fn renderer() -> ScreenRenderer<Frame> {
+        ScreenRenderer::new(Frame::new(ColorCode::new(Color::Yellow, Color::Black)))
+    }
+
+    // Bars show up as `#`, trailing blanks are left out
+    fn lines(frame: &Frame) -> Vec<String> {
+        (0..BUFFER_HEIGHT)
+            .map(|row| {
+                let line: String = frame
+                    .row(row)
+                    .iter()
+                    .map(|c| match c.ascii_character {
+                        0xfe => '#',
+                        byte => byte as char,
+                    })
+                    .collect();
+                line.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    const KICK_OFF: [&str; BUFFER_HEIGHT] = [
+        "                                       0:0",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+        " #                                                                            #",
+        " #                                                                            #",
+        " #                                      @                                     #",
+        " #                                                                            #",
+        " #                                                                            #",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+    ];
+
+    const LATER: [&str; BUFFER_HEIGHT] = [
+        "                                      12:3",
+        "",
+        "",
+        "",
+        "",
+        "                    @",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+        " #",
+        " #                                                                            #",
+        " #                                                                            #",
+        " #                                                                            #",
+        " #                                                                            #",
+        " #                                                                            #",
+        " #                                                                            #",
+        "                                                                              #",
+        "",
+        "",
+        "",
+        "",
+        "",
+    ];
+
+    #[test]
+    fn kick_off() {
+        let state = game();
+        let mut renderer = renderer();
+
+        renderer.render_score(state.score);
+        state.render(&mut renderer);
+
+        assert_eq!(lines(renderer.surface()), KICK_OFF);
+    }
+
+    #[test]
+    fn redraw_only_keeps_the_latest_positions() {
+        let mut state = game();
+        let mut renderer = renderer();
+        renderer.render_score(state.score);
+        state.render(&mut renderer);
+
+        state.config.set_paddle_height(7);
+        state.player1.position.y = 15;
+        state.player2.position.y = 16;
+        state.ball.position.x = Fixed::from_num(20.5);
+        state.ball.position.y = Fixed::from_num(5.5);
+        state.score = (12, 3);
+        renderer.render_score(state.score);
+        state.render(&mut renderer);
+
+        assert_eq!(lines(renderer.surface()), LATER);
+    }
+
+    #[test]
+    fn bars_use_the_player_colors() {
+        let mut state = game();
+        let mut renderer = renderer();
+        state.config.set_color(Color::LightRed, Player::Player2);
+
+        state.render(&mut renderer);
+
+        let bar = renderer.surface().read_screen_char_at_pos(12, 78);
+        assert_eq!(bar.color_code, ColorCode::new(Color::LightRed, Color::Black));
+        let ball = renderer.surface().read_screen_char_at_pos(12, 40);
+        assert_eq!(ball.color_code, ColorCode::new(Color::Yellow, Color::Black));
+    }
+}
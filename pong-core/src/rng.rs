@@ -0,0 +1,69 @@
+/// Source of the "random" numbers used for serves and the mistakes of the CPU
+pub trait Rng {
+    fn next_u8(&mut self) -> u8;
+}
+
+const MULTIPLIER: u64 = 6364136223846793005;
+// Stream 54, same as the demo of the reference implementation,
+// so the numbers can be checked against it
+const INCREMENT: u64 = (54 << 1) | 1;
+
+/// PCG32 (XSH RR, see pcg-random.org). Small and fast, and the same
+/// seed always gives the same numbers, so a match can be played again.
+#[derive(Clone, Debug)]
+pub struct Pcg32 {
+    seed: u64,
+    state: u64,
+}
+
+impl Pcg32 {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Self { seed, state: 0 };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    /// What this was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(MULTIPLIER).wrapping_add(INCREMENT);
+
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rotation = (old >> 59) as u32;
+        xorshifted.rotate_right(rotation)
+    }
+}
+
+impl Rng for Pcg32 {
+    fn next_u8(&mut self) -> u8 {
+        // The high bits are the good ones
+        (self.next_u32() >> 24) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_reference_implementation() {
+        let mut rng = Pcg32::new(42);
+        let numbers: Vec<u32> = (0..6).map(|_| rng.next_u32()).collect();
+
+        assert_eq!(numbers, [0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]);
+    }
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let mut first = Pcg32::new(0x1234_5678_9abc_def0);
+        let mut second = Pcg32::new(first.seed());
+
+        assert!((0..1000).all(|_| first.next_u8() == second.next_u8()));
+    }
+}
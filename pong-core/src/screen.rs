@@ -0,0 +1,98 @@
+// Text mode screen: 80x25 cells of a character and its colors
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+#[allow(dead_code)]
+pub enum Color {
+    Black = 0,
+    Blue = 1,
+    Green = 2,
+    Cyan = 3,
+    Red = 4,
+    Magenta = 5,
+    Brown = 6,
+    LightGray = 7,
+    DarkGray = 8,
+    LightBlue = 9,
+    LightGreen = 10,
+    LightCyan = 11,
+    LightRed = 12,
+    Pink = 13,
+    Yellow = 14,
+    White = 15,
+}
+
+impl From<u8> for Color {
+    fn from(n: u8) -> Self {
+        use Color::*;
+        match n {
+            0 => Black,
+            1 => Blue,
+            2 => Green,
+            3 => Cyan,
+            4 => Red,
+            5 => Magenta,
+            6 => Brown,
+            7 => LightGray,
+            8 => DarkGray,
+            9 => LightBlue,
+            10 => LightGreen,
+            11 => LightCyan,
+            12 => LightRed,
+            13 => Pink,
+            14 => Yellow,
+            15 => White,
+            _ => panic!("Invalid Color")
+        }
+    }
+}
+
+impl Color {
+    pub fn next_color(&self) -> Color {
+        let mut val = *self as u8;
+        if val > 14 {
+            val = 1;
+        }
+        val += 1;
+        val.into()
+    }
+
+    pub fn previous_color(&self) -> Color {
+        let mut val = *self as u8;
+        if val < 2 {
+            val = 15;
+        }
+
+        val -= 1;
+
+        val.into()
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct ColorCode(pub u8);
+
+impl ColorCode {
+    pub fn new(foreground: Color, background: Color) -> ColorCode {
+        ColorCode((background as u8) << 4 | (foreground as u8))
+    }
+
+    pub fn with_background(self, background: Color) -> ColorCode {
+        ColorCode((background as u8) << 4 | (self.0 & 0x0f))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct ScreenChar {
+    pub ascii_character: u8,
+    pub color_code: ColorCode,
+}
+
+// There are 25 rows
+pub const BUFFER_HEIGHT: usize = 25;
+
+// There are 80 columns
+pub const BUFFER_WIDTH: usize = 80;
@@ -0,0 +1,153 @@
+use crate::screen::{Color, ColorCode, ScreenChar, BUFFER_HEIGHT, BUFFER_WIDTH};
+
+/// Something with `BUFFER_HEIGHT` x `BUFFER_WIDTH` cells of `ScreenChar`,
+/// like the VGA text buffer. Only the first three methods have to be provided.
+pub trait Surface {
+    fn write_screen_char_at_pos(&mut self, row: usize, col: usize, screen_char: ScreenChar);
+
+    fn read_screen_char_at_pos(&self, row: usize, col: usize) -> ScreenChar;
+
+    /// Colors used by the methods that don't take a `ColorCode`
+    fn color_code(&self) -> ColorCode;
+
+    fn write_byte_at_pos(&mut self, row: usize, col: usize, byte: u8) {
+        let color_code = self.color_code();
+        self.write_screen_char_at_pos(row, col, ScreenChar {
+            ascii_character: byte,
+            color_code,
+        });
+    }
+
+    fn write_string_at_pos(&mut self, row: usize, mut col: usize, s: &str) {
+        s.bytes().for_each(|byte| {
+            match byte {
+                0x20..=0x7e => self.write_byte_at_pos(row, col, byte),
+                _ => self.write_byte_at_pos(row, col, 0xfe),
+            };
+            col += 1;
+            if col > BUFFER_WIDTH - 1 {
+                col = 0;
+            }
+        });
+    }
+
+    fn write_colored_string_at_pos(&mut self, row: usize, col: usize, s: &str, color_code: ColorCode) {
+        s.bytes().enumerate().for_each(|(i, byte)| {
+            let ascii_character = match byte {
+                0x20..=0x7e => byte,
+                _ => 0xfe,
+            };
+            self.write_screen_char_at_pos(row, (col + i) % BUFFER_WIDTH, ScreenChar { ascii_character, color_code });
+        });
+    }
+
+    fn write_bytes_at_pos(&mut self, row: usize, mut col: usize, bs: &[u8]) {
+        bs.iter().for_each(|byte| {
+            match byte {
+                0x20..=0x7e => self.write_byte_at_pos(row, col, *byte),
+                _ => self.write_byte_at_pos(row, col, 0xfe),
+            }
+            col += 1;
+        });
+    }
+}
+
+/// A screen that only lives in memory, e.g. to check what the game drew
+#[derive(Clone)]
+pub struct Frame {
+    chars: [[ScreenChar; BUFFER_WIDTH]; BUFFER_HEIGHT],
+    color_code: ColorCode,
+}
+
+impl Frame {
+    /// An empty screen in `color_code`
+    pub fn new(color_code: ColorCode) -> Self {
+        let blank = ScreenChar {
+            ascii_character: b' ',
+            color_code,
+        };
+        Self {
+            chars: [[blank; BUFFER_WIDTH]; BUFFER_HEIGHT],
+            color_code,
+        }
+    }
+
+    pub fn row(&self, row: usize) -> &[ScreenChar; BUFFER_WIDTH] {
+        &self.chars[row]
+    }
+
+    /// Whatever `surface` shows right now
+    pub fn copy_of(surface: &impl Surface) -> Self {
+        let mut frame = Self::new(surface.color_code());
+        for row in 0..BUFFER_HEIGHT {
+            for col in 0..BUFFER_WIDTH {
+                frame.chars[row][col] = surface.read_screen_char_at_pos(row, col);
+            }
+        }
+        frame
+    }
+
+    /// Cells (row, col, new char) that are different from `previous`
+    pub fn changes<'a>(&'a self, previous: &'a Frame) -> impl Iterator<Item = (usize, usize, ScreenChar)> + 'a {
+        self.chars.iter().zip(previous.chars.iter()).enumerate().flat_map(|(row, (new, old))| {
+            new.iter()
+                .zip(old.iter())
+                .enumerate()
+                .filter(|(_, (new, old))| new != old)
+                .map(move |(col, (new, _))| (row, col, *new))
+        })
+    }
+
+    /// Same as `Writer::set_background` in the kernel
+    pub fn set_background(&mut self, background: Color) {
+        self.color_code = self.color_code.with_background(background);
+        self.chars.iter_mut().flatten().for_each(|character| {
+            character.color_code = character.color_code.with_background(background);
+        });
+    }
+}
+
+impl Surface for Frame {
+    fn write_screen_char_at_pos(&mut self, row: usize, col: usize, screen_char: ScreenChar) {
+        self.chars[row][col] = screen_char;
+    }
+
+    fn read_screen_char_at_pos(&self, row: usize, col: usize) -> ScreenChar {
+        self.chars[row][col]
+    }
+
+    fn color_code(&self) -> ColorCode {
+        self.color_code
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_changed_cells_are_reported() {
+        let previous = Frame::new(ColorCode::new(Color::Yellow, Color::Black));
+        let mut frame = previous.clone();
+        frame.write_byte_at_pos(0, 40, b':');
+        frame.write_string_at_pos(24, 78, "ab");
+        // Writing what is already there isn't a change
+        frame.write_byte_at_pos(12, 12, b' ');
+
+        let changes: Vec<_> = frame.changes(&previous).map(|(row, col, c)| (row, col, c.ascii_character)).collect();
+
+        assert_eq!(changes, [(0, 40, b':'), (24, 78, b'a'), (24, 79, b'b')]);
+        assert_eq!(frame.changes(&frame).count(), 0);
+    }
+
+    #[test]
+    fn background_changes_every_cell() {
+        let previous = Frame::new(ColorCode::new(Color::Yellow, Color::Black));
+        let mut frame = previous.clone();
+
+        frame.set_background(Color::Blue);
+
+        assert_eq!(frame.changes(&previous).count(), BUFFER_WIDTH * BUFFER_HEIGHT);
+        assert_eq!(frame.read_screen_char_at_pos(3, 3).color_code, ColorCode::new(Color::Yellow, Color::Blue));
+    }
+}
@@ -1 +1 @@
-qemu-system-x86_64 -drive format=raw,file="target\x86_64-atiw\debug\bootimage-pong.bin"
\ No newline at end of file
+qemu-system-x86_64 -drive format=raw,file="target\x86_64-atiw\debug\bootimage-pong.bin" -serial stdio
\ No newline at end of file
@@ -0,0 +1,165 @@
+use x86_64::instructions::port::Port;
+
+// Primary IDE channel, the one QEMU puts its first two drives on
+const DATA_PORT: u16 = 0x1f0;
+const SECTOR_COUNT_PORT: u16 = 0x1f2;
+const LBA_LOW_PORT: u16 = 0x1f3;
+const LBA_MID_PORT: u16 = 0x1f4;
+const LBA_HIGH_PORT: u16 = 0x1f5;
+const DRIVE_PORT: u16 = 0x1f6;
+// Reading gives the status, writing sends a command
+const COMMAND_PORT: u16 = 0x1f7;
+// Reading gives the status without acknowledging an interrupt
+const CONTROL_PORT: u16 = 0x3f6;
+
+// Status bits
+const ERROR: u8 = 1 << 0;
+const DATA_REQUEST: u8 = 1 << 3;
+const DRIVE_FAULT: u8 = 1 << 5;
+const BUSY: u8 = 1 << 7;
+
+// Commands
+const READ_SECTORS: u8 = 0x20;
+const WRITE_SECTORS: u8 = 0x30;
+const CACHE_FLUSH: u8 = 0xe7;
+const IDENTIFY: u8 = 0xec;
+
+// Nothing here waits for IRQ14, and the IDT doesn't have an entry for it
+const NO_INTERRUPTS: u8 = 1 << 1;
+// LBA addressing, plus the two bits that are always set
+const DRIVE_LBA: u8 = 0xe0;
+
+// What the status reads as when nothing at all is connected to the channel
+const FLOATING_BUS: u8 = 0xff;
+
+pub const SECTOR_SIZE: usize = 512;
+
+// Polls of the status register before giving up, like in `ps2`
+const TIMEOUT: u32 = 100_000;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Drive {
+    Master,
+    Slave,
+}
+
+#[derive(Debug)]
+pub enum AtaError {
+    /// Nothing answered, or it's not a disk (a CD drive for example)
+    NoDrive,
+    /// The drive stayed busy
+    Timeout,
+    /// The drive reported an error, this is its status
+    Failed(u8),
+}
+
+fn status() -> u8 {
+    let mut port: Port<u8> = Port::new(COMMAND_PORT);
+    unsafe { port.read() }
+}
+
+// Selecting a drive takes 400ns to settle, reading the
+// alternate status 4 times is the usual way to wait that long
+fn select(drive: Drive, lba: u32) {
+    let mut control: Port<u8> = Port::new(CONTROL_PORT);
+    let mut select: Port<u8> = Port::new(DRIVE_PORT);
+    let drive_bit = match drive {
+        Drive::Master => 0,
+        Drive::Slave => 1 << 4,
+    };
+    unsafe {
+        control.write(NO_INTERRUPTS);
+        select.write(DRIVE_LBA | drive_bit | ((lba >> 24) & 0x0f) as u8);
+        (0..4).for_each(|_| {
+            control.read();
+        });
+    }
+}
+
+fn wait_while_busy() -> Result<u8, AtaError> {
+    (0..TIMEOUT)
+        .map(|_| status())
+        .find(|status| status & BUSY == 0)
+        .ok_or(AtaError::Timeout)
+}
+
+fn wait_for_data() -> Result<(), AtaError> {
+    let status = (0..TIMEOUT)
+        .map(|_| status())
+        .find(|status| status & BUSY == 0 && status & (DATA_REQUEST | ERROR | DRIVE_FAULT) != 0)
+        .ok_or(AtaError::Timeout)?;
+    check(status)
+}
+
+fn check(status: u8) -> Result<(), AtaError> {
+    if status & (ERROR | DRIVE_FAULT) != 0 {
+        Err(AtaError::Failed(status))
+    } else {
+        Ok(())
+    }
+}
+
+// LBA28, one sector at a time is all the game needs
+fn command(drive: Drive, lba: u32, command: u8) -> Result<(), AtaError> {
+    select(drive, lba);
+    if status() == FLOATING_BUS {
+        return Err(AtaError::NoDrive);
+    }
+    wait_while_busy()?;
+    unsafe {
+        Port::<u8>::new(SECTOR_COUNT_PORT).write(1);
+        Port::<u8>::new(LBA_LOW_PORT).write(lba as u8);
+        Port::<u8>::new(LBA_MID_PORT).write((lba >> 8) as u8);
+        Port::<u8>::new(LBA_HIGH_PORT).write((lba >> 16) as u8);
+        Port::<u8>::new(COMMAND_PORT).write(command);
+    }
+    Ok(())
+}
+
+/// Checks that `drive` is there and is a disk, gives its number of sectors
+pub fn identify(drive: Drive) -> Result<u32, AtaError> {
+    command(drive, 0, IDENTIFY)?;
+    if status() == 0 {
+        return Err(AtaError::NoDrive);
+    }
+    wait_while_busy()?;
+    // Drives that don't speak ATA put their signature here
+    let signature = unsafe { (Port::<u8>::new(LBA_MID_PORT).read(), Port::<u8>::new(LBA_HIGH_PORT).read()) };
+    if signature != (0, 0) {
+        return Err(AtaError::NoDrive);
+    }
+    wait_for_data()?;
+
+    let mut data: Port<u16> = Port::new(DATA_PORT);
+    let mut words = [0u16; SECTOR_SIZE / 2];
+    words.iter_mut().for_each(|word| *word = unsafe { data.read() });
+    // Words 60 and 61 are the number of sectors LBA28 can reach
+    Ok(words[60] as u32 | (words[61] as u32) << 16)
+}
+
+pub fn read_sector(drive: Drive, lba: u32, buffer: &mut [u8; SECTOR_SIZE]) -> Result<(), AtaError> {
+    command(drive, lba, READ_SECTORS)?;
+    wait_for_data()?;
+
+    let mut data: Port<u16> = Port::new(DATA_PORT);
+    buffer.chunks_exact_mut(2).for_each(|bytes| {
+        let word = unsafe { data.read() };
+        bytes.copy_from_slice(&word.to_le_bytes());
+    });
+    Ok(())
+}
+
+/// Only returns once the sector is out of the cache of the drive
+pub fn write_sector(drive: Drive, lba: u32, buffer: &[u8; SECTOR_SIZE]) -> Result<(), AtaError> {
+    command(drive, lba, WRITE_SECTORS)?;
+    wait_for_data()?;
+
+    let mut data: Port<u16> = Port::new(DATA_PORT);
+    buffer.chunks_exact(2).for_each(|bytes| unsafe {
+        data.write(u16::from_le_bytes([bytes[0], bytes[1]]));
+    });
+
+    wait_while_busy().and_then(check)?;
+    command(drive, lba, CACHE_FLUSH)?;
+    wait_while_busy().and_then(check)
+}
@@ -0,0 +1,35 @@
+use x86_64::instructions::interrupts;
+use x86_64::instructions::port::Port;
+
+// Register to read goes to the first port, its value comes out of the second
+const ADDRESS_PORT: u16 = 0x70;
+const DATA_PORT: u16 = 0x71;
+
+// Real time clock, in whatever format the firmware picked (BCD or binary)
+pub const RTC_SECONDS: u8 = 0x00;
+pub const RTC_MINUTES: u8 = 0x02;
+pub const RTC_HOURS: u8 = 0x04;
+pub const RTC_DAY: u8 = 0x07;
+pub const RTC_MONTH: u8 = 0x08;
+pub const RTC_YEAR: u8 = 0x09;
+
+pub fn read(register: u8) -> u8 {
+    let mut address = Port::<u8>::new(ADDRESS_PORT);
+    let mut data = Port::<u8>::new(DATA_PORT);
+
+    // Nobody else may select a register before we got the value
+    interrupts::without_interrupts(|| unsafe {
+        address.write(register);
+        data.read()
+    })
+}
+
+pub fn write(register: u8, value: u8) {
+    let mut address = Port::<u8>::new(ADDRESS_PORT);
+    let mut data = Port::<u8>::new(DATA_PORT);
+
+    interrupts::without_interrupts(|| unsafe {
+        address.write(register);
+        data.write(value);
+    })
+}
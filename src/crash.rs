@@ -0,0 +1,128 @@
+use core::fmt::{self, Write};
+use core::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use x86_64::structures::idt::InterruptStackFrame;
+
+use crate::serial::SERIAL1;
+use crate::vga_buffer::{Color, ColorCode, ScreenChar, Surface, Writer, BUFFER_HEIGHT, BUFFER_WIDTH, WRITER};
+use crate::{hlt_loop, STATE_LOCATION};
+
+// The score lives in `GameLoop`, which an exception handler can't get to,
+// so the game loop leaves a copy here
+static SCORE: (AtomicU32, AtomicU32) = (AtomicU32::new(0), AtomicU32::new(0));
+
+pub fn record_score(score: (u32, u32)) {
+    SCORE.0.store(score.0, Ordering::Relaxed);
+    SCORE.1.store(score.1, Ordering::Relaxed);
+}
+
+// Same for the seed of the match, so the crash can be played again
+static SEED: AtomicU64 = AtomicU64::new(0);
+
+pub fn record_seed(seed: u64) {
+    SEED.store(seed, Ordering::Relaxed);
+}
+
+/// Replaces the game with a report about `exception` on screen and on
+/// serial, then stops for good. `details` is anything the handler decoded.
+pub fn crash(exception: &str, stack_frame: &InterruptStackFrame, details: fmt::Arguments) -> ! {
+    // We might have interrupted the main loop while it was drawing or logging.
+    // It never runs again, so nobody else is going to unlock these.
+    unsafe {
+        WRITER.force_unlock();
+        SERIAL1.force_unlock();
+    }
+    // Otherwise whatever note was playing goes on forever
+    crate::sound::silence();
+
+    {
+        let mut serial = SERIAL1.lock();
+        write_report(&mut *serial, exception, stack_frame, details).unwrap_or(());
+    }
+    {
+        let mut writer = WRITER.lock();
+        let mut screen = CrashScreen::new(&mut writer);
+        write_report(&mut screen, exception, stack_frame, details).unwrap_or(());
+    }
+
+    hlt_loop();
+}
+
+fn write_report(
+    out: &mut impl Write,
+    exception: &str,
+    stack_frame: &InterruptStackFrame,
+    details: fmt::Arguments,
+) -> fmt::Result {
+    writeln!(out, "EXCEPTION: {}", exception)?;
+    writeln!(out)?;
+    writeln!(out, "{}", details)?;
+    writeln!(out)?;
+    writeln!(out, "{:#?}", stack_frame)?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "State: {:?}   Score: {}:{}   Seed: {:#018x}",
+        STATE_LOCATION.read(),
+        SCORE.0.load(Ordering::Relaxed),
+        SCORE.1.load(Ordering::Relaxed),
+        SEED.load(Ordering::Relaxed),
+    )
+}
+
+// White on blue, so nobody mistakes it for part of the game
+const CRASH_COLORS: ColorCode = ColorCode((Color::Blue as u8) << 4 | Color::White as u8);
+const MARGIN: usize = 2;
+
+/// Writes lines onto a blank screen, starting in the top left corner
+struct CrashScreen<'a> {
+    writer: &'a mut Writer,
+    row: usize,
+    col: usize,
+}
+
+impl<'a> CrashScreen<'a> {
+    fn new(writer: &'a mut Writer) -> Self {
+        (0..BUFFER_HEIGHT).for_each(|row| {
+            (0..BUFFER_WIDTH).for_each(|col| {
+                writer.write_screen_char_at_pos(row, col, ScreenChar {
+                    ascii_character: b' ',
+                    color_code: CRASH_COLORS,
+                });
+            })
+        });
+
+        Self {
+            writer,
+            row: 1,
+            col: MARGIN,
+        }
+    }
+}
+
+impl Write for CrashScreen<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            if byte == b'\n' || self.col >= BUFFER_WIDTH - MARGIN {
+                self.row += 1;
+                self.col = MARGIN;
+            }
+            // Whatever doesn't fit is still on serial
+            if self.row >= BUFFER_HEIGHT {
+                return Ok(());
+            }
+            if byte != b'\n' {
+                let ascii_character = match byte {
+                    0x20..=0x7e => byte,
+                    _ => 0xfe,
+                };
+                self.writer.write_screen_char_at_pos(self.row, self.col, ScreenChar {
+                    ascii_character,
+                    color_code: CRASH_COLORS,
+                });
+                self.col += 1;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use log::{info, warn};
+use pong_core::record::{self, RECORD_LEN};
+use pong_core::HighScores;
+
+use crate::ata::{self, Drive, SECTOR_SIZE};
+use crate::state::GameConfig;
+
+// The boot image is the master, the game gets a disk of its own
+const DRIVE: Drive = Drive::Slave;
+// Sector 0 stays free for a partition table, in case the image ever gets one
+const RECORD_SECTOR: u32 = 1;
+
+/// Keeps the settings and high scores across reboots, see `pong_core::record`.
+/// Without a disk everything still works, it's just forgotten.
+pub struct SaveDisk {
+    present: bool,
+    // What's on the disk right now, unchanged records aren't written again
+    saved: [u8; RECORD_LEN],
+}
+
+impl SaveDisk {
+    pub fn open() -> Self {
+        let present = match ata::identify(DRIVE) {
+            Ok(sectors) => sectors > RECORD_SECTOR,
+            Err(err) => {
+                info!("No disk for settings and high scores: {:?}", err);
+                false
+            }
+        };
+        Self {
+            present,
+            saved: [0; RECORD_LEN],
+        }
+    }
+
+    /// Whether there's a disk to save to at all
+    pub fn present(&self) -> bool {
+        self.present
+    }
+
+    /// What was saved last time, `None` if there's no disk or it can't be read
+    pub fn load(&mut self) -> Option<(GameConfig, HighScores)> {
+        if !self.present {
+            return None;
+        }
+
+        let mut sector = [0; SECTOR_SIZE];
+        if let Err(err) = ata::read_sector(DRIVE, RECORD_SECTOR, &mut sector) {
+            warn!("Couldn't read the saved settings: {:?}", err);
+            return None;
+        }
+        match record::decode(&sector) {
+            Ok(loaded) => {
+                self.saved.copy_from_slice(&sector[..RECORD_LEN]);
+                info!("Loaded the saved settings and high scores");
+                Some(loaded)
+            }
+            Err(err) => {
+                warn!("Saved settings are unusable: {:?}", err);
+                None
+            }
+        }
+    }
+
+    /// Blocks until the drive is done, so not something to do during a match
+    pub fn save(&mut self, config: &GameConfig, high_scores: &HighScores) {
+        let record = record::encode(config, high_scores);
+        if !self.present || record == self.saved {
+            return;
+        }
+
+        let mut sector = [0; SECTOR_SIZE];
+        sector[..RECORD_LEN].copy_from_slice(&record);
+        match ata::write_sector(DRIVE, RECORD_SECTOR, &sector) {
+            Ok(()) => self.saved = record,
+            Err(err) => warn!("Couldn't save the settings: {:?}", err),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Everything the interrupt handlers tell the main loop about
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Event {
+    Tick,
+    Scancode(u8),
+    /// One byte of a packet from the PS/2 mouse, see `mouse::MouseDecoder`
+    MouseByte(u8),
+}
+
+// Events are stored as u16 so a slot can be copied in one go:
+// the high byte is the kind of event, the low byte its payload
+const TICK: u16 = 0x0100;
+const SCANCODE: u16 = 0x0200;
+const MOUSE_BYTE: u16 = 0x0300;
+
+impl From<Event> for u16 {
+    fn from(event: Event) -> Self {
+        match event {
+            Event::Tick => TICK,
+            Event::Scancode(scancode) => SCANCODE | scancode as u16,
+            Event::MouseByte(byte) => MOUSE_BYTE | byte as u16,
+        }
+    }
+}
+
+impl From<u16> for Event {
+    fn from(raw: u16) -> Self {
+        match raw & 0xff00 {
+            TICK => Event::Tick,
+            SCANCODE => Event::Scancode((raw & 0xff) as u8),
+            MOUSE_BYTE => Event::MouseByte((raw & 0xff) as u8),
+            _ => panic!("Invalid Event"),
+        }
+    }
+}
+
+// Has to be a power of two, so the indices can wrap around `usize::MAX`
+pub const CAPACITY: usize = 256;
+
+/// Lock-free ring buffer between the interrupt handlers and the main loop.
+///
+/// There is exactly one consumer (the main loop). The producers are the
+/// interrupt handlers, which never run at the same time because the CPU
+/// clears the interrupt flag while handling one, so `push` never races itself.
+pub struct EventQueue {
+    buffer: UnsafeCell<[u16; CAPACITY]>,
+    // Next slot to write, only moved by `push`
+    head: AtomicUsize,
+    // Next slot to read, only moved by `pop`
+    tail: AtomicUsize,
+}
+
+// See the comment on `EventQueue` for why this is fine
+unsafe impl Sync for EventQueue {}
+
+impl EventQueue {
+    pub const fn new() -> Self {
+        Self {
+            buffer: UnsafeCell::new([0; CAPACITY]),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        let tail = self.tail.load(Ordering::Acquire);
+        let head = self.head.load(Ordering::Acquire);
+        head.wrapping_sub(tail)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Only call this from interrupt handlers.
+    /// Returns `false` and drops the event if the queue is full.
+    pub fn push(&self, event: Event) -> bool {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        if head.wrapping_sub(tail) >= CAPACITY {
+            return false;
+        }
+
+        unsafe {
+            (*self.buffer.get())[head % CAPACITY] = event.into();
+        }
+        // Publishes the slot written above
+        self.head.store(head.wrapping_add(1), Ordering::Release);
+        true
+    }
+
+    /// Only call this from the main loop
+    pub fn pop(&self) -> Option<Event> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(Ordering::Acquire);
+        if tail == head {
+            return None;
+        }
+
+        let raw = unsafe { (*self.buffer.get())[tail % CAPACITY] };
+        // Hands the slot back to `push`
+        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+        Some(raw.into())
+    }
+}
+
+pub static EVENTS: EventQueue = EventQueue::new();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn events_come_out_in_order() {
+        let queue = EventQueue::new();
+        assert!(queue.push(Event::Scancode(0x11)));
+        assert!(queue.push(Event::Tick));
+        assert_eq!(queue.len(), 2);
+
+        assert_eq!(queue.pop(), Some(Event::Scancode(0x11)));
+        assert_eq!(queue.pop(), Some(Event::Tick));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test_case]
+    fn full_queue_drops_new_events() {
+        let queue = EventQueue::new();
+        (0..CAPACITY).for_each(|_| assert!(queue.push(Event::Tick)));
+
+        assert!(!queue.push(Event::Scancode(0x1f)));
+        assert_eq!(queue.len(), CAPACITY);
+        // Popping makes room again
+        queue.pop();
+        assert!(queue.push(Event::Scancode(0x1f)));
+    }
+}
@@ -0,0 +1,283 @@
+use pc_keyboard::{KeyCode, KeyEvent, KeyState};
+use x86_64::instructions::interrupts;
+
+use pong_core::bindings::Action;
+use pong_core::pongbar::{Key, PongBar};
+use pong_core::{HighScores, Renderer};
+
+use crate::crash;
+use crate::disk::SaveDisk;
+use crate::nvram;
+use crate::events::{Event, EVENTS};
+use crate::interrupts::TICK_HZ;
+use crate::keyboard::LayoutKeyboard;
+use crate::state::{
+    clear_game_over_text, clear_menu_text, clear_pause_text, render_game_over_text, render_menu_text,
+    render_pause_text, GameConfig, GameState, Menu as MainMenu, MenuAction, MenuPage, VgaRenderer,
+    StateLocation::{GameOver, Menu, Paused, Running},
+};
+use crate::rng::{self, Pcg32};
+use crate::mouse::MouseDecoder;
+use crate::sound::SoundQueue;
+use crate::ui::{Accumulator, TimerClock};
+use crate::vga_buffer::BackBuffer;
+use crate::STATE_LOCATION;
+
+/// Owns the game and drives it from the events the interrupt handlers queue up.
+/// Nothing in here runs inside an interrupt handler, so it can't deadlock
+/// with one over `WRITER` or the game state.
+pub struct GameLoop {
+    state: GameState,
+    menu: MainMenu,
+    renderer: VgaRenderer,
+    keyboard: LayoutKeyboard,
+    physics: Accumulator,
+    frames: Accumulator,
+    sound: SoundQueue,
+    mouse: MouseDecoder,
+    disk: SaveDisk,
+}
+
+impl Default for GameLoop {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameLoop {
+    pub fn new() -> Self {
+        // Loaded here rather than in `GameState::new`, `pong_core` has no hardware to load from
+        let mut disk = SaveDisk::open();
+        let (config, high_scores) = disk.load().unwrap_or_else(|| {
+            // Without a usable disk, the CMOS RAM may still have a few of the settings
+            let mut config = GameConfig::default();
+            nvram::load(&mut config);
+            (config, HighScores::default())
+        });
+        let mut state = GameState::new(config, Pcg32::new(rng::hardware_seed()), TimerClock);
+        state.high_scores = high_scores;
+
+        let mut game_loop = Self {
+            keyboard: LayoutKeyboard::new(state.config.layout()),
+            state,
+            menu: MainMenu::new(),
+            renderer: VgaRenderer::new(BackBuffer::new()),
+            physics: Accumulator::new(),
+            frames: Accumulator::new(),
+            sound: SoundQueue::new(),
+            mouse: MouseDecoder::new(),
+            disk,
+        };
+        let background = game_loop.state.config.background();
+        game_loop.renderer.surface_mut().set_background(background);
+        render_menu_text(game_loop.renderer.surface_mut(), &game_loop.state, &game_loop.menu);
+        game_loop.renderer.surface_mut().present();
+        game_loop
+    }
+
+    pub fn run(&mut self) -> ! {
+        loop {
+            // Checking the queue and halting has to happen without interrupts,
+            // otherwise an event arriving in between would only be handled
+            // after the next one wakes us up
+            interrupts::disable();
+            match EVENTS.pop() {
+                Some(event) => {
+                    interrupts::enable();
+                    self.handle_event(event);
+                }
+                None => interrupts::enable_and_hlt(),
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::Tick => self.tick(),
+            Event::Scancode(scancode) => {
+                if let Ok(Some(key_event)) = self.keyboard.add_byte(scancode) {
+                    if STATE_LOCATION.read() == Menu {
+                        if let KeyState::Down = key_event.state {
+                            self.handle_menu_key(key_event.code);
+                        }
+                    } else {
+                        self.handle_game_key(key_event);
+                    }
+                }
+            }
+            Event::MouseByte(byte) => {
+                if let Some(packet) = self.mouse.add_byte(byte) {
+                    if STATE_LOCATION.read() == Running {
+                        self.state.mouse_moved(packet.dy);
+                    }
+                }
+            }
+        }
+        // For the crash screen, see `crash::record_score`
+        crash::record_score(self.state.score);
+    }
+
+    fn tick(&mut self) {
+        let state = &mut self.state;
+
+        // Physics runs at a fixed rate, independent of how often we draw
+        let steps = self.physics.advance(state.config.physics_rate(), TICK_HZ);
+        let frames = self.frames.advance(state.config.target_fps(), TICK_HZ);
+
+        match STATE_LOCATION.read() {
+            Menu => {
+                if frames > 0 {
+                    state.show_menu(&mut self.renderer)
+                }
+            },
+            Running => {
+                for _ in 0..steps {
+                    let events = state.step();
+                    if !state.config.muted() {
+                        self.sound.play_events(&events);
+                    }
+                    if events.scored.is_some() {
+                        self.renderer.render_score(state.score);
+                    }
+                    // A point may have ended the match
+                    if let Some(winner) = events.winner {
+                        STATE_LOCATION.set(GameOver);
+                        render_game_over_text(self.renderer.surface_mut(), winner, &state.config);
+                        // Between matches, so the disk can take its time
+                        self.disk.save(&state.config, &state.high_scores);
+                        break;
+                    }
+                }
+                if frames > 0 && STATE_LOCATION.read() == Running {
+                    state.render(&mut self.renderer);
+                }
+            },
+            Paused | GameOver => ()
+        }
+
+        // Everything drawn since the last frame, menu and banners included
+        if frames > 0 {
+            self.renderer.surface_mut().present();
+        }
+
+        self.sound.tick(1000 / TICK_HZ);
+    }
+
+    fn handle_menu_key(&mut self, code: KeyCode) {
+        let state = &mut self.state;
+        let page = self.menu.page();
+        let action = self.menu.handle_key(code, &mut state.config);
+        // Settings are saved once they're done with, not on every change
+        if page != MenuPage::Main && self.menu.page() == MenuPage::Main {
+            save_settings(&mut self.disk, state);
+        }
+        match action {
+            MenuAction::StartGame => {
+                // The main page has settings as well
+                save_settings(&mut self.disk, state);
+                clear_menu_text(self.renderer.surface_mut());
+                start_match(state);
+                self.renderer.render_score(state.score);
+                STATE_LOCATION.set(Running);
+            },
+            MenuAction::Redraw => {
+                let surface = self.renderer.surface_mut();
+                surface.set_background(state.config.background());
+                render_menu_text(surface, state, &self.menu);
+
+                // Keys already held down are forgotten, which is fine in the menu
+                if self.keyboard.layout() != state.config.layout() {
+                    self.keyboard = LayoutKeyboard::new(state.config.layout());
+                }
+            },
+            MenuAction::None => (),
+        }
+    }
+
+    // What a key does comes from `GameConfig::bindings`, set on the controls page
+    fn handle_game_key(&mut self, key_event: KeyEvent) {
+        let state = &mut self.state;
+        let action = match state.config.bindings().action(key_event.code) {
+            Some(action) => action,
+            None => return,
+        };
+        match (action, key_event.state) {
+            //////////////////////////////////////////////////////
+            //                  Player1                         //
+            //////////////////////////////////////////////////////
+            (Action::P1Up, key_state) => hold(&mut state.player1, Key::Up, key_state),
+            (Action::P1Down, key_state) => hold(&mut state.player1, Key::Down, key_state),
+
+            //////////////////////////////////////////////////////
+            //                  Player2                         //
+            //////////////////////////////////////////////////////
+            (Action::P2Up, key_state) => hold(&mut state.player2, Key::Up, key_state),
+            (Action::P2Down, key_state) => hold(&mut state.player2, Key::Down, key_state),
+
+            (Action::Pause, KeyState::Down) => {
+                match STATE_LOCATION.read() {
+                    Running => {
+                        self.sound.stop();
+                        render_pause_text(self.renderer.surface_mut());
+                        STATE_LOCATION.set(Paused);
+                    },
+                    Paused => {
+                        clear_pause_text(self.renderer.surface_mut());
+                        STATE_LOCATION.set(Running);
+                    },
+                    Menu | GameOver => (),
+                }
+            },
+            (Action::Rematch, KeyState::Down) => {
+                if let GameOver = STATE_LOCATION.read() {
+                    // Rematch with the same settings
+                    clear_game_over_text(self.renderer.surface_mut());
+                    start_match(state);
+                    self.renderer.render_score(state.score);
+                    STATE_LOCATION.set(Running);
+                }
+            },
+            (Action::Menu, KeyState::Down) => {
+                if let GameOver = STATE_LOCATION.read() {
+                    clear_game_over_text(self.renderer.surface_mut());
+                }
+                if let Paused = STATE_LOCATION.read() {
+                    clear_pause_text(self.renderer.surface_mut());
+                }
+                self.sound.stop();
+                state.reset();
+
+                self.menu = MainMenu::new();
+                render_menu_text(self.renderer.surface_mut(), state, &self.menu);
+
+                STATE_LOCATION.set(Menu);
+            }
+
+            _ => {}
+        }
+    }
+}
+
+// Waits for the hardware, so only ever from the menu
+fn save_settings(disk: &mut SaveDisk, state: &GameState) {
+    if disk.present() {
+        disk.save(&state.config, &state.high_scores);
+    } else {
+        nvram::save(&state.config);
+    }
+}
+
+// Every match gets its own seed, see `rng::new_match_rng`
+fn start_match(state: &mut GameState) {
+    state.start_match(rng::new_match_rng());
+    crash::record_seed(state.rng().seed());
+}
+
+// The bar keeps moving from `GameState::step` until the key is released,
+// so the typematic repeat of the keyboard doesn't matter
+fn hold(bar: &mut PongBar, key: Key, state: KeyState) {
+    match state {
+        KeyState::Down => bar.press(key),
+        KeyState::Up => bar.release(key),
+    }
+}
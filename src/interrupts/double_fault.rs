@@ -1,8 +1,12 @@
 use x86_64::structures::idt::InterruptStackFrame;
 
+use crate::crash::crash;
+
+// Runs on its own stack, see `gdt::DOUBLE_FAULT_IST_INDEX`
 pub extern "x86-interrupt" fn double_fault_handler(
     stack_frame: &mut InterruptStackFrame,
     _error_code: u64,
 ) -> ! {
-    panic!("EXCEPTION: DOUBLE FAULT\n{:#?}", stack_frame);
+    // The error code is always 0
+    crash("DOUBLE FAULT", stack_frame, format_args!("An exception happened while handling another one"));
 }
\ No newline at end of file
@@ -0,0 +1,91 @@
+use x86_64::registers::control::Cr2;
+use x86_64::structures::idt::{InterruptStackFrame, PageFaultErrorCode};
+
+use crate::crash::crash;
+
+// None of these can be recovered from (yet), they all end on the crash screen
+
+pub extern "x86-interrupt" fn divide_error_handler(stack_frame: &mut InterruptStackFrame) {
+    crash("DIVIDE ERROR", stack_frame, format_args!("Division by zero or result too large"));
+}
+
+pub extern "x86-interrupt" fn overflow_handler(stack_frame: &mut InterruptStackFrame) {
+    crash("OVERFLOW", stack_frame, format_args!("INTO with the overflow flag set"));
+}
+
+pub extern "x86-interrupt" fn bound_range_exceeded_handler(stack_frame: &mut InterruptStackFrame) {
+    crash("BOUND RANGE EXCEEDED", stack_frame, format_args!("Index outside of the BOUND range"));
+}
+
+pub extern "x86-interrupt" fn invalid_opcode_handler(stack_frame: &mut InterruptStackFrame) {
+    crash("INVALID OPCODE", stack_frame, format_args!("Instruction at the instruction pointer is invalid"));
+}
+
+pub extern "x86-interrupt" fn device_not_available_handler(stack_frame: &mut InterruptStackFrame) {
+    crash("DEVICE NOT AVAILABLE", stack_frame, format_args!("FPU/SSE instruction without an FPU"));
+}
+
+pub extern "x86-interrupt" fn invalid_tss_handler(stack_frame: &mut InterruptStackFrame, error_code: u64) {
+    crash("INVALID TSS", stack_frame, format_args!("{}", SelectorErrorCode(error_code)));
+}
+
+pub extern "x86-interrupt" fn segment_not_present_handler(stack_frame: &mut InterruptStackFrame, error_code: u64) {
+    crash("SEGMENT NOT PRESENT", stack_frame, format_args!("{}", SelectorErrorCode(error_code)));
+}
+
+pub extern "x86-interrupt" fn stack_segment_fault_handler(stack_frame: &mut InterruptStackFrame, error_code: u64) {
+    crash("STACK SEGMENT FAULT", stack_frame, format_args!("{}", SelectorErrorCode(error_code)));
+}
+
+pub extern "x86-interrupt" fn general_protection_fault_handler(stack_frame: &mut InterruptStackFrame, error_code: u64) {
+    crash("GENERAL PROTECTION FAULT", stack_frame, format_args!("{}", SelectorErrorCode(error_code)));
+}
+
+pub extern "x86-interrupt" fn page_fault_handler(stack_frame: &mut InterruptStackFrame, error_code: PageFaultErrorCode) {
+    // CR2 holds the address that couldn't be accessed
+    crash(
+        "PAGE FAULT",
+        stack_frame,
+        format_args!("Accessed address: {:?}\nError code: {:?}", Cr2::read(), error_code),
+    );
+}
+
+pub extern "x86-interrupt" fn x87_floating_point_handler(stack_frame: &mut InterruptStackFrame) {
+    crash("x87 FLOATING POINT", stack_frame, format_args!("Unmasked x87 FPU exception"));
+}
+
+pub extern "x86-interrupt" fn alignment_check_handler(stack_frame: &mut InterruptStackFrame, error_code: u64) {
+    crash("ALIGNMENT CHECK", stack_frame, format_args!("Unaligned access, error code {:#x}", error_code));
+}
+
+pub extern "x86-interrupt" fn simd_floating_point_handler(stack_frame: &mut InterruptStackFrame) {
+    crash("SIMD FLOATING POINT", stack_frame, format_args!("Unmasked SSE exception"));
+}
+
+/// Error code of the exceptions caused by loading a segment selector
+struct SelectorErrorCode(u64);
+
+impl core::fmt::Display for SelectorErrorCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if self.0 == 0 {
+            return write!(f, "Error code: 0 (not caused by a segment selector)");
+        }
+
+        // Bit 0: happened while delivering an external interrupt
+        // Bits 1-2: the table the selector points into
+        // Bits 3-15: index into that table
+        let external = self.0 & 0b1 != 0;
+        let table = match (self.0 >> 1) & 0b11 {
+            0b00 => "GDT",
+            0b10 => "LDT",
+            _ => "IDT",
+        };
+        let index = (self.0 >> 3) & 0x1fff;
+
+        write!(f, "Error code: {:#x}\nSelector: {} index {}", self.0, table, index)?;
+        if external {
+            write!(f, " (external event)")?;
+        }
+        Ok(())
+    }
+}
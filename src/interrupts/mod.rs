@@ -1,16 +1,23 @@
 mod keyboard;
+mod mouse;
 mod timer;
+mod pit;
 mod breakpoint;
 mod double_fault;
+mod exceptions;
 
 
 pub use keyboard::*;
+pub use mouse::*;
 use pic8259_simple::ChainedPics;
 use spin::Mutex;
 pub use timer::*;
+pub use pit::*;
 pub use breakpoint::breakpoint_handler;
 pub use double_fault::double_fault_handler;
+pub use exceptions::*;
 use lazy_static::lazy_static;
+use x86_64::instructions::port::Port;
 use x86_64::structures::idt::InterruptDescriptorTable;
 
 
@@ -24,9 +31,27 @@ lazy_static! {
     static ref IDT: InterruptDescriptorTable = {
         let mut idt = InterruptDescriptorTable::new();
         idt.breakpoint.set_handler_fn(breakpoint_handler);
-        idt.double_fault.set_handler_fn(double_fault_handler);
+        idt.divide_error.set_handler_fn(divide_error_handler);
+        idt.overflow.set_handler_fn(overflow_handler);
+        idt.bound_range_exceeded.set_handler_fn(bound_range_exceeded_handler);
+        idt.invalid_opcode.set_handler_fn(invalid_opcode_handler);
+        idt.device_not_available.set_handler_fn(device_not_available_handler);
+        idt.invalid_tss.set_handler_fn(invalid_tss_handler);
+        idt.segment_not_present.set_handler_fn(segment_not_present_handler);
+        idt.stack_segment_fault.set_handler_fn(stack_segment_fault_handler);
+        idt.general_protection_fault.set_handler_fn(general_protection_fault_handler);
+        idt.page_fault.set_handler_fn(page_fault_handler);
+        idt.x87_floating_point.set_handler_fn(x87_floating_point_handler);
+        idt.alignment_check.set_handler_fn(alignment_check_handler);
+        idt.simd_floating_point.set_handler_fn(simd_floating_point_handler);
+        unsafe {
+            idt.double_fault
+                .set_handler_fn(double_fault_handler)
+                .set_stack_index(crate::gdt::DOUBLE_FAULT_IST_INDEX);
+        }
         idt[InterruptIndex::Timer.as_usize()].set_handler_fn(timer_interrupt_handler);
         idt[InterruptIndex::Keyboard.as_usize()].set_handler_fn(keyboard_interrupt_handler);
+        idt[InterruptIndex::Mouse.as_usize()].set_handler_fn(mouse_interrupt_handler);
         idt
     };
 }
@@ -35,11 +60,31 @@ pub fn init_idt() {
     IDT.load();
 }
 
+// Data ports of the two PICs, writing one sets its interrupt mask
+const PIC_1_DATA: u16 = 0x21;
+const PIC_2_DATA: u16 = 0xa1;
+
+/// Lets IRQ12 through, the BIOS may have masked it.
+/// `ChainedPics` only restores the masks it found, so this goes to the ports directly
+pub fn unmask_mouse_irq() {
+    let mut pic_1: Port<u8> = Port::new(PIC_1_DATA);
+    let mut pic_2: Port<u8> = Port::new(PIC_2_DATA);
+    unsafe {
+        // IRQ2 is where the slave PIC is chained to the master
+        let mask = pic_1.read();
+        pic_1.write(mask & !(1 << 2));
+        let mask = pic_2.read();
+        pic_2.write(mask & !(1 << (12 - 8)));
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 #[repr(u8)]
 pub enum InterruptIndex {
     Timer = PIC_1_OFFSET,
     Keyboard,
+    // IRQ12, on the slave PIC
+    Mouse = PIC_2_OFFSET + 4,
 }
 
 impl InterruptIndex {
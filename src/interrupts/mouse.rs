@@ -0,0 +1,18 @@
+use x86_64::structures::idt::InterruptStackFrame;
+use x86_64::instructions::port::Port;
+
+use crate::events::{Event, EVENTS};
+use super::{InterruptIndex, PICS};
+
+// Same as for the keyboard, the main loop puts the packets together
+pub extern "x86-interrupt" fn mouse_interrupt_handler(_stack_frame: &mut InterruptStackFrame) {
+    let mut port = Port::new(0x60);
+
+    let byte: u8 = unsafe { port.read() };
+    EVENTS.push(Event::MouseByte(byte));
+
+    unsafe {
+        PICS.lock()
+            .notify_end_of_interrupt(InterruptIndex::Mouse.as_u8());
+    }
+}
@@ -0,0 +1,46 @@
+use x86_64::instructions::port::Port;
+
+// The oscillator feeding all PIT channels
+const BASE_FREQUENCY: u32 = 1_193_182;
+
+const CHANNEL_0: u16 = 0x40;
+const CHANNEL_2: u16 = 0x42;
+const COMMAND: u16 = 0x43;
+
+/// Timer interrupts per second once `set_pit_frequency` ran
+pub const TICK_HZ: u32 = 1000;
+
+/// Programs PIT channel 0 (IRQ0) to fire `hz` times per second.
+/// The BIOS default is about 18.2 Hz
+pub fn set_pit_frequency(hz: u32) {
+    let divisor = divisor(hz);
+
+    let mut command: Port<u8> = Port::new(COMMAND);
+    let mut channel_0: Port<u8> = Port::new(CHANNEL_0);
+    unsafe {
+        // channel 0, lobyte/hibyte, mode 3 (square wave), binary
+        command.write(0x36);
+        channel_0.write((divisor & 0xff) as u8);
+        channel_0.write((divisor >> 8) as u8);
+    }
+}
+
+/// Programs PIT channel 2, the one wired to the PC speaker,
+/// to a square wave of `hz`. See `sound` for turning the speaker on
+pub fn set_speaker_frequency(hz: u32) {
+    let divisor = divisor(hz);
+
+    let mut command: Port<u8> = Port::new(COMMAND);
+    let mut channel_2: Port<u8> = Port::new(CHANNEL_2);
+    unsafe {
+        // channel 2, lobyte/hibyte, mode 3 (square wave), binary
+        command.write(0xb6);
+        channel_2.write((divisor & 0xff) as u8);
+        channel_2.write((divisor >> 8) as u8);
+    }
+}
+
+fn divisor(hz: u32) -> u16 {
+    // A divisor of 0 would mean 65536 to the PIT, so stay in range
+    (BASE_FREQUENCY / hz.max(1)).max(1).min(u16::MAX as u32) as u16
+}
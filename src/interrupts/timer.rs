@@ -2,36 +2,22 @@ use core::sync::atomic::Ordering;
 
 use x86_64::structures::idt::InterruptStackFrame;
 
+use crate::events::{Event, CAPACITY, EVENTS};
 use super::InterruptIndex;
 
 pub extern "x86-interrupt" fn timer_interrupt_handler(_stack_frame: &mut InterruptStackFrame) {
     {
-        use crate::{STATE, TIMER, STATE_LOCATION};
-        use crate::state::StateLocation::{Menu, Running, Paused};
-        let mut number = TIMER.number.fetch_add(1, Ordering::Acquire);
-        if number == 0 {
-            number = 1;
-        }
+        use crate::TIMER;
+        TIMER.ticks.fetch_add(1, Ordering::Relaxed);
 
-        match STATE_LOCATION.read() {
-            Menu => {
-                if number % 3 == 0 {
-                    STATE.show_menu()
-                }
-             } ,
-            Running => {
-                if number % 3 == 0 {
-                    STATE.step();
-                }
-            },
-            Paused => ()
+        // If the main loop falls behind, rather lose ticks than key presses
+        if EVENTS.len() < CAPACITY / 2 {
+            EVENTS.push(Event::Tick);
         }
-
-        
     }
 
     unsafe {
         super::PICS.lock()
             .notify_end_of_interrupt(InterruptIndex::Timer.as_u8());
     }
-}
\ No newline at end of file
+}
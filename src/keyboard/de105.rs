@@ -0,0 +1,47 @@
+use pc_keyboard::{layouts::Us104Key, DecodedKey, HandleControl, KeyCode, KeyboardLayout, Modifiers};
+
+/// German 105 key layout (QWERTZ), which `pc_keyboard` 0.5 doesn't have.
+/// Keys printed the same as on a US keyboard are left to `Us104Key`
+pub struct De105Key;
+
+impl KeyboardLayout for De105Key {
+    fn map_keycode(keycode: KeyCode, modifiers: &Modifiers, handle_ctrl: HandleControl) -> DecodedKey {
+        let (plain, shifted, alt_gr) = match keycode {
+            KeyCode::BackTick => ('^', '°', None),
+            KeyCode::Key2 => ('2', '"', Some('²')),
+            KeyCode::Key3 => ('3', '§', Some('³')),
+            KeyCode::Key6 => ('6', '&', None),
+            KeyCode::Key7 => ('7', '/', Some('{')),
+            KeyCode::Key8 => ('8', '(', Some('[')),
+            KeyCode::Key9 => ('9', ')', Some(']')),
+            KeyCode::Key0 => ('0', '=', Some('}')),
+            KeyCode::Minus => ('ß', '?', Some('\\')),
+            KeyCode::Equals => ('´', '`', None),
+            KeyCode::BracketSquareRight => ('+', '*', Some('~')),
+            // Set 1 calls the key left of Enter `BackSlash`, like `Uk105Key` expects
+            KeyCode::HashTilde | KeyCode::BackSlash => ('#', '\'', None),
+            KeyCode::Comma => (',', ';', None),
+            KeyCode::Fullstop => ('.', ':', None),
+            KeyCode::Slash => ('-', '_', None),
+            KeyCode::BracketSquareLeft => return umlaut('ü', 'Ü', modifiers),
+            KeyCode::SemiColon => return umlaut('ö', 'Ö', modifiers),
+            KeyCode::Quote => return umlaut('ä', 'Ä', modifiers),
+            KeyCode::Q if modifiers.alt_gr => return DecodedKey::Unicode('@'),
+            KeyCode::E if modifiers.alt_gr => return DecodedKey::Unicode('€'),
+            // Swapped with a US keyboard, Ctrl included
+            KeyCode::Y => return Us104Key::map_keycode(KeyCode::Z, modifiers, handle_ctrl),
+            KeyCode::Z => return Us104Key::map_keycode(KeyCode::Y, modifiers, handle_ctrl),
+            _ => return Us104Key::map_keycode(keycode, modifiers, handle_ctrl),
+        };
+
+        DecodedKey::Unicode(match alt_gr {
+            Some(c) if modifiers.alt_gr => c,
+            _ if modifiers.is_shifted() => shifted,
+            _ => plain,
+        })
+    }
+}
+
+fn umlaut(lower: char, upper: char, modifiers: &Modifiers) -> DecodedKey {
+    DecodedKey::Unicode(if modifiers.is_caps() { upper } else { lower })
+}
@@ -0,0 +1,125 @@
+mod de105;
+
+use alloc::format;
+use alloc::string::String;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use pc_keyboard::{
+    layouts, DecodedKey, Error, HandleControl, KeyCode, KeyEvent, Keyboard, KeyboardLayout, Modifiers, ScancodeSet,
+    ScancodeSet1, ScancodeSet2,
+};
+
+use crate::ps2::{self, CONFIG_TRANSLATION};
+use crate::state::Layout;
+use de105::De105Key;
+
+// Set by `detect_scancode_set`, the controller translates to set 1 unless told otherwise
+static SCANCODE_SET_2: AtomicBool = AtomicBool::new(false);
+
+/// Checks whether the 8042 translates the keyboard's scancodes to set 1.
+/// Has to run before interrupts are enabled, like `mouse::init`
+pub fn detect_scancode_set() {
+    match ps2::config() {
+        Ok(config) => {
+            let set_2 = config & CONFIG_TRANSLATION == 0;
+            SCANCODE_SET_2.store(set_2, Ordering::Relaxed);
+            log::info!("Keyboard uses scancode set {}", if set_2 { 2 } else { 1 });
+        }
+        Err(error) => log::warn!("Couldn't read the PS/2 configuration, assuming scancode set 1: {:?}", error),
+    }
+}
+
+/// A `pc_keyboard::Keyboard` whose layout can be changed while the game runs
+pub struct LayoutKeyboard {
+    layout: Layout,
+    keyboard: AnySet,
+}
+
+impl LayoutKeyboard {
+    pub fn new(layout: Layout) -> Self {
+        let keyboard = if SCANCODE_SET_2.load(Ordering::Relaxed) {
+            AnySet::Set2(AnyLayout::new(layout, ScancodeSet2))
+        } else {
+            AnySet::Set1(AnyLayout::new(layout, ScancodeSet1))
+        };
+        Self { layout, keyboard }
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    pub fn add_byte(&mut self, byte: u8) -> Result<Option<KeyEvent>, Error> {
+        match &mut self.keyboard {
+            AnySet::Set1(keyboard) => keyboard.add_byte(byte),
+            AnySet::Set2(keyboard) => keyboard.add_byte(byte),
+        }
+    }
+}
+
+/// How the menu shows `code`: the character it types in `layout`, as printed on
+/// the key, or the name of the `KeyCode` if that can't be drawn.
+/// Bindings stay with the key wherever it is, the layout only changes its name
+pub fn key_label(layout: Layout, code: KeyCode) -> String {
+    // Without Num Lock the keypad doesn't look like the number row
+    let modifiers = Modifiers {
+        lshift: false,
+        rshift: false,
+        lctrl: false,
+        rctrl: false,
+        numlock: false,
+        capslock: false,
+        alt_gr: false,
+    };
+    let decoded = match layout {
+        Layout::Us104Key => layouts::Us104Key::map_keycode(code, &modifiers, HandleControl::Ignore),
+        Layout::Uk105Key => layouts::Uk105Key::map_keycode(code, &modifiers, HandleControl::Ignore),
+        Layout::De105Key => De105Key::map_keycode(code, &modifiers, HandleControl::Ignore),
+        Layout::Azerty => layouts::Azerty::map_keycode(code, &modifiers, HandleControl::Ignore),
+        Layout::Dvorak104Key => layouts::Dvorak104Key::map_keycode(code, &modifiers, HandleControl::Ignore),
+    };
+    match decoded {
+        // VGA text mode only gets ASCII from us
+        DecodedKey::Unicode(c) if c.is_ascii_graphic() => format!("{}", c.to_ascii_uppercase()),
+        _ => format!("{:?}", code),
+    }
+}
+
+// `pc_keyboard` picks the layout and scancode set at compile time,
+// so every combination needs its own variant
+enum AnySet {
+    Set1(AnyLayout<ScancodeSet1>),
+    Set2(AnyLayout<ScancodeSet2>),
+}
+
+enum AnyLayout<S: ScancodeSet> {
+    Us104Key(Keyboard<layouts::Us104Key, S>),
+    Uk105Key(Keyboard<layouts::Uk105Key, S>),
+    De105Key(Keyboard<De105Key, S>),
+    Azerty(Keyboard<layouts::Azerty, S>),
+    Dvorak104Key(Keyboard<layouts::Dvorak104Key, S>),
+}
+
+impl<S: ScancodeSet> AnyLayout<S> {
+    fn new(layout: Layout, set: S) -> Self {
+        match layout {
+            Layout::Us104Key => AnyLayout::Us104Key(Keyboard::new(layouts::Us104Key, set, HandleControl::Ignore)),
+            Layout::Uk105Key => AnyLayout::Uk105Key(Keyboard::new(layouts::Uk105Key, set, HandleControl::Ignore)),
+            Layout::De105Key => AnyLayout::De105Key(Keyboard::new(De105Key, set, HandleControl::Ignore)),
+            Layout::Azerty => AnyLayout::Azerty(Keyboard::new(layouts::Azerty, set, HandleControl::Ignore)),
+            Layout::Dvorak104Key => {
+                AnyLayout::Dvorak104Key(Keyboard::new(layouts::Dvorak104Key, set, HandleControl::Ignore))
+            }
+        }
+    }
+
+    fn add_byte(&mut self, byte: u8) -> Result<Option<KeyEvent>, Error> {
+        match self {
+            AnyLayout::Us104Key(keyboard) => keyboard.add_byte(byte),
+            AnyLayout::Uk105Key(keyboard) => keyboard.add_byte(byte),
+            AnyLayout::De105Key(keyboard) => keyboard.add_byte(byte),
+            AnyLayout::Azerty(keyboard) => keyboard.add_byte(byte),
+            AnyLayout::Dvorak104Key(keyboard) => keyboard.add_byte(byte),
+        }
+    }
+}
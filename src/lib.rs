@@ -0,0 +1,160 @@
+#![no_std]
+#![cfg_attr(test, no_main)]
+#![feature(abi_x86_interrupt)]
+#![feature(custom_test_frameworks)]
+#![feature(alloc_error_handler)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+pub mod serial;
+pub mod allocator;
+pub mod memory;
+pub mod mouse;
+pub mod ps2;
+pub mod crash;
+pub mod cmos;
+pub mod ata;
+pub mod disk;
+pub mod nvram;
+pub mod state;
+pub mod events;
+pub mod game_loop;
+pub mod gdt;
+pub mod interrupts;
+pub mod keyboard;
+pub mod rng;
+pub mod sound;
+pub mod ui;
+pub mod vga_buffer;
+mod state_location;
+
+use ui::Timer;
+use state::GlobalStateLocation;
+
+
+// Globals
+lazy_static! {
+    pub static ref STATE_LOCATION: GlobalStateLocation = GlobalStateLocation::default();
+}
+
+lazy_static! {
+    pub static ref TIMER: Timer = Timer::new();
+}
+
+use bootloader::BootInfo;
+use core::panic::PanicInfo;
+use lazy_static::lazy_static;
+use memory::BootInfoFrameAllocator;
+use x86_64::VirtAddr;
+
+pub fn init() {
+    serial::init_logger();
+    gdt::init();
+    interrupts::init_idt();
+    unsafe { interrupts::PICS.lock().initialize() };
+    interrupts::set_pit_frequency(interrupts::TICK_HZ);
+    match mouse::init() {
+        Ok(()) => {
+            interrupts::unmask_mouse_irq();
+            log::info!("PS/2 mouse enabled");
+        }
+        Err(error) => log::warn!("No PS/2 mouse: {:?}", error),
+    }
+    keyboard::detect_scancode_set();
+    x86_64::instructions::interrupts::enable();
+    log::info!("Interrupts enabled, PIT at {} Hz", interrupts::TICK_HZ);
+}
+
+/// Sets up paging and the heap, call `init` first so a fault in here
+/// ends up on the crash screen
+pub fn init_memory(boot_info: &'static BootInfo) {
+    let physical_memory_offset = VirtAddr::new(boot_info.physical_memory_offset);
+    let mut mapper = unsafe { memory::init(physical_memory_offset) };
+    let mut frame_allocator = unsafe { BootInfoFrameAllocator::init(&boot_info.memory_map) };
+
+    allocator::init_heap(&mut mapper, &mut frame_allocator).expect("heap initialization failed");
+}
+
+#[alloc_error_handler]
+fn alloc_error_handler(layout: alloc::alloc::Layout) -> ! {
+    panic!("allocation error: {:?}", layout)
+}
+
+// Looping through the [hlt instruction](https://en.wikipedia.org/wiki/HLT_(x86_instruction))
+// uses a lot less resouces than looping around a no-op (e.g. `()`)
+pub fn hlt_loop() -> ! {
+    loop {
+        x86_64::instructions::hlt();
+    }
+}
+
+//////////////////////////////////////////////////////
+//                  Tests                           //
+//////////////////////////////////////////////////////
+// `cargo test` boots every test binary in QEMU (see `test-args` in Cargo.toml),
+// results go to the serial port and QEMU is closed with the outcome
+
+/// Exit codes for QEMU's `isa-debug-exit` device,
+/// QEMU itself exits with `(code << 1) | 1`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum QemuExitCode {
+    Success = 0x10,
+    Failed = 0x11,
+}
+
+pub fn exit_qemu(exit_code: QemuExitCode) {
+    use x86_64::instructions::port::Port;
+
+    // `iobase` of the isa-debug-exit device
+    let mut port = Port::new(0xf4);
+    unsafe {
+        port.write(exit_code as u32);
+    }
+}
+
+pub trait Testable {
+    fn run(&self);
+}
+
+impl<T: Fn()> Testable for T {
+    fn run(&self) {
+        serial_print!("{}...\t", core::any::type_name::<T>());
+        self();
+        serial_println!("[ok]");
+    }
+}
+
+pub fn test_runner(tests: &[&dyn Testable]) {
+    serial_println!("Running {} tests", tests.len());
+    for test in tests {
+        test.run();
+    }
+    exit_qemu(QemuExitCode::Success);
+}
+
+pub fn test_panic_handler(info: &PanicInfo) -> ! {
+    serial_println!("[failed]\n");
+    serial_println!("Error: {}\n", info);
+    exit_qemu(QemuExitCode::Failed);
+    hlt_loop();
+}
+
+#[cfg(test)]
+bootloader::entry_point!(test_kernel_main);
+
+#[cfg(test)]
+fn test_kernel_main(boot_info: &'static BootInfo) -> ! {
+    init();
+    init_memory(boot_info);
+    test_main();
+    hlt_loop();
+}
+
+#[cfg(test)]
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    test_panic_handler(info)
+}
@@ -1,81 +1,41 @@
 #![no_std]
 #![no_main]
-#![feature(abi_x86_interrupt)]
 #![feature(custom_test_frameworks)]
-#![test_runner(crate::test_runner)]
-
-#[cfg(test)]
-fn test_runner(tests: &[&dyn Fn()]) {
-    println!("Running {} tests", tests.len());
-    for test in tests {
-        test();
-    }
-}
+#![test_runner(pong::test_runner)]
+#![reexport_test_harness_main = "test_main"]
 
+use bootloader::{entry_point, BootInfo};
+use core::panic::PanicInfo;
 
-mod state;
-mod interrupts;
-mod pongball;
-mod pongbar;
-mod ui;
-mod vga_buffer;
-mod state_location;
+use pong::game_loop::GameLoop;
 
-use ui::Timer;
-use state::{GameState, GlobalStateLocation, render_score};
+// Checks the signature of `kernel_main`, which a bare `_start` couldn't
+entry_point!(kernel_main);
 
+fn kernel_main(boot_info: &'static BootInfo) -> ! {
+    pong::init();
+    pong::init_memory(boot_info);
 
-// Globals
-lazy_static! {
-    pub static ref STATE_LOCATION: GlobalStateLocation = GlobalStateLocation::default();
-}
-
-lazy_static! {
-    pub static ref STATE: GameState = {
-        x86_64::instructions::hlt();
-        GameState::new()
-    };
-}
+    #[cfg(test)]
+    test_main();
 
-lazy_static! {
-    pub static ref TIMER: Timer = Timer::new();
+    // The game state lives here now, the interrupt handlers only queue events
+    let mut game = GameLoop::new();
+    game.run();
 }
 
-use core::{fmt::Write, panic::PanicInfo};
-use lazy_static::lazy_static;
-
-
-
-
-
-#[no_mangle]
-pub extern "C" fn _start() -> ! {
-    init();
-    {
-        let score = STATE.score.read();
-        render_score(*score);
-    }
-
-
-    hlt_loop();
-}
+#[cfg(not(test))]
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    use core::fmt::Write;
 
-// Looping through the [hlt instruction](https://en.wikipedia.org/wiki/HLT_(x86_instruction))
-// uses a lot less resouces than looping around a no-op (e.g. `()`)
-fn hlt_loop() -> ! {
-    loop {
-        x86_64::instructions::hlt();
-    }
+    log::error!("{}", info);
+    write!(pong::vga_buffer::WRITER.lock(), "panic: {}", info).unwrap_or(());
+    pong::hlt_loop();
 }
 
+#[cfg(test)]
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
-    write!(vga_buffer::WRITER.lock(), "panic: {}", info).unwrap_or(());
-    hlt_loop();
-}
-
-pub fn init() {
-    interrupts::init_idt();
-    unsafe { interrupts::PICS.lock().initialize() };
-    x86_64::instructions::interrupts::enable();
+    pong::test_panic_handler(info)
 }
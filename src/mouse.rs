@@ -0,0 +1,120 @@
+use crate::ps2::{self, Ps2Error};
+
+// Commands for the mouse itself
+const SET_DEFAULTS: u8 = 0xf6;
+const ENABLE_REPORTING: u8 = 0xf4;
+
+/// Gets the PS/2 mouse to send packets on IRQ12.
+/// Has to run before interrupts are enabled, otherwise the interrupt handlers
+/// take the answers of the mouse off the controller before we see them
+pub fn init() -> Result<(), Ps2Error> {
+    ps2::enable_aux_port()?;
+    ps2::send_to_aux(SET_DEFAULTS)?;
+    ps2::send_to_aux(ENABLE_REPORTING)
+}
+
+// Bits of the first byte of a packet
+const LEFT_BUTTON: u8 = 1 << 0;
+const RIGHT_BUTTON: u8 = 1 << 1;
+const MIDDLE_BUTTON: u8 = 1 << 2;
+const ALWAYS_SET: u8 = 1 << 3;
+const X_NEGATIVE: u8 = 1 << 4;
+const Y_NEGATIVE: u8 = 1 << 5;
+const X_OVERFLOW: u8 = 1 << 6;
+const Y_OVERFLOW: u8 = 1 << 7;
+
+/// Movement since the last packet in mouse counts, up and right are positive
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct MousePacket {
+    pub dx: i16,
+    pub dy: i16,
+    pub left: bool,
+    pub right: bool,
+    pub middle: bool,
+}
+
+/// Puts the bytes of `Event::MouseByte` back together into packets of three
+pub struct MouseDecoder {
+    bytes: [u8; 3],
+    received: usize,
+}
+
+impl Default for MouseDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MouseDecoder {
+    pub fn new() -> Self {
+        Self {
+            bytes: [0; 3],
+            received: 0,
+        }
+    }
+
+    /// The whole packet once its last byte arrived
+    pub fn add_byte(&mut self, byte: u8) -> Option<MousePacket> {
+        // A first byte without `ALWAYS_SET` means we lost track
+        // of where packets start, so wait for one that could be
+        if self.received == 0 && byte & ALWAYS_SET == 0 {
+            return None;
+        }
+
+        self.bytes[self.received] = byte;
+        self.received += 1;
+        if self.received < self.bytes.len() {
+            return None;
+        }
+        self.received = 0;
+
+        let flags = self.bytes[0];
+        Some(MousePacket {
+            dx: movement(self.bytes[1], flags & X_NEGATIVE != 0, flags & X_OVERFLOW != 0),
+            dy: movement(self.bytes[2], flags & Y_NEGATIVE != 0, flags & Y_OVERFLOW != 0),
+            left: flags & LEFT_BUTTON != 0,
+            right: flags & RIGHT_BUTTON != 0,
+            middle: flags & MIDDLE_BUTTON != 0,
+        })
+    }
+}
+
+// 9 bit two's complement, the sign lives in the first byte.
+// The value of an overflowed movement is garbage, so it's dropped
+fn movement(value: u8, negative: bool, overflow: bool) -> i16 {
+    if overflow {
+        0
+    } else if negative {
+        value as i16 - 0x100
+    } else {
+        value as i16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn packets_are_decoded() {
+        let mut decoder = MouseDecoder::new();
+
+        assert_eq!(decoder.add_byte(ALWAYS_SET | Y_NEGATIVE | LEFT_BUTTON), None);
+        assert_eq!(decoder.add_byte(5), None);
+        let packet = decoder.add_byte(0xfe);
+
+        assert_eq!(packet, Some(MousePacket { dx: 5, dy: -2, left: true, ..MousePacket::default() }));
+    }
+
+    #[test_case]
+    fn stray_bytes_are_skipped() {
+        let mut decoder = MouseDecoder::new();
+
+        // Tail of a packet we only saw half of
+        assert_eq!(decoder.add_byte(0x10), None);
+        assert_eq!(decoder.add_byte(ALWAYS_SET | X_OVERFLOW), None);
+        assert_eq!(decoder.add_byte(0x80), None);
+
+        assert_eq!(decoder.add_byte(3), Some(MousePacket { dy: 3, ..MousePacket::default() }));
+    }
+}
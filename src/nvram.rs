@@ -0,0 +1,38 @@
+use log::{info, warn};
+use pong_core::record::{self, RecordError, BLOB_LEN};
+
+use crate::cmos;
+use crate::state::GameConfig;
+
+// The BIOS keeps its own things in the first half of the CMOS RAM and guards
+// them with a checksum. QEMU and SeaBIOS only use a few bytes from 0x5b up
+// in the second half, so the blob goes right at its start.
+const FIRST_REGISTER: u8 = 0x40;
+
+fn read_blob() -> [u8; BLOB_LEN] {
+    let mut blob = [0; BLOB_LEN];
+    blob.iter_mut()
+        .zip(FIRST_REGISTER..)
+        .for_each(|(byte, register)| *byte = cmos::read(register));
+    blob
+}
+
+/// Puts the settings kept in the CMOS RAM into `config`, see `record::encode_blob`.
+/// Only used when the disk has nothing to load, see `disk::SaveDisk`.
+pub fn load(config: &mut GameConfig) {
+    match record::decode_blob(&read_blob(), config) {
+        Ok(()) => info!("Loaded the settings from the CMOS RAM"),
+        Err(RecordError::NoRecord) => info!("No settings in the CMOS RAM yet"),
+        Err(err) => warn!("Settings in the CMOS RAM are unusable, using the defaults: {:?}", err),
+    }
+}
+
+pub fn save(config: &GameConfig) {
+    let blob = record::encode_blob(config);
+    if blob == read_blob() {
+        return;
+    }
+    blob.iter()
+        .zip(FIRST_REGISTER..)
+        .for_each(|(&byte, register)| cmos::write(register, byte));
+}
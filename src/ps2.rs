@@ -0,0 +1,116 @@
+use x86_64::instructions::port::Port;
+
+// The 8042 PS/2 controller, keyboard and mouse both hang off it
+const DATA_PORT: u16 = 0x60;
+// Reading gives the status, writing sends a command to the controller itself
+const COMMAND_PORT: u16 = 0x64;
+
+// Status bits
+const OUTPUT_FULL: u8 = 1 << 0;
+const INPUT_FULL: u8 = 1 << 1;
+
+// Controller commands
+const READ_CONFIG: u8 = 0x20;
+const WRITE_CONFIG: u8 = 0x60;
+const ENABLE_AUX: u8 = 0xa8;
+const WRITE_TO_AUX: u8 = 0xd4;
+
+// Bits of the configuration byte
+pub const CONFIG_KEYBOARD_IRQ: u8 = 1 << 0;
+pub const CONFIG_AUX_IRQ: u8 = 1 << 1;
+pub const CONFIG_AUX_CLOCK_DISABLED: u8 = 1 << 5;
+pub const CONFIG_TRANSLATION: u8 = 1 << 6;
+
+// Devices answer every command with this
+const ACK: u8 = 0xfa;
+
+// Polls of the status register before giving up, so a missing
+// device can't hang the boot. Way more than QEMU or real hardware need
+const TIMEOUT: u32 = 100_000;
+
+#[derive(Debug)]
+pub enum Ps2Error {
+    /// The controller or the device didn't answer in time
+    Timeout,
+    /// The device answered with something other than `ACK`
+    NoAck(u8),
+}
+
+fn status() -> u8 {
+    let mut port: Port<u8> = Port::new(COMMAND_PORT);
+    unsafe { port.read() }
+}
+
+fn wait_until_writable() -> Result<(), Ps2Error> {
+    (0..TIMEOUT)
+        .find(|_| status() & INPUT_FULL == 0)
+        .map(|_| ())
+        .ok_or(Ps2Error::Timeout)
+}
+
+fn wait_until_readable() -> Result<(), Ps2Error> {
+    (0..TIMEOUT)
+        .find(|_| status() & OUTPUT_FULL != 0)
+        .map(|_| ())
+        .ok_or(Ps2Error::Timeout)
+}
+
+fn command(command: u8) -> Result<(), Ps2Error> {
+    wait_until_writable()?;
+    let mut port: Port<u8> = Port::new(COMMAND_PORT);
+    unsafe { port.write(command) };
+    Ok(())
+}
+
+fn write_data(byte: u8) -> Result<(), Ps2Error> {
+    wait_until_writable()?;
+    let mut port: Port<u8> = Port::new(DATA_PORT);
+    unsafe { port.write(byte) };
+    Ok(())
+}
+
+fn read_data() -> Result<u8, Ps2Error> {
+    wait_until_readable()?;
+    let mut port: Port<u8> = Port::new(DATA_PORT);
+    Ok(unsafe { port.read() })
+}
+
+// Throws away whatever the devices sent before we were ready for it
+fn flush() {
+    let mut port: Port<u8> = Port::new(DATA_PORT);
+    (0..16)
+        .take_while(|_| status() & OUTPUT_FULL != 0)
+        .for_each(|_| unsafe {
+            port.read();
+        });
+}
+
+/// The controller's configuration byte, see the `CONFIG_*` bits
+pub fn config() -> Result<u8, Ps2Error> {
+    command(READ_CONFIG)?;
+    read_data()
+}
+
+fn set_config(config: u8) -> Result<(), Ps2Error> {
+    command(WRITE_CONFIG)?;
+    write_data(config)
+}
+
+/// Turns on the second (auxiliary) port of the controller and its IRQ12
+pub fn enable_aux_port() -> Result<(), Ps2Error> {
+    flush();
+    command(ENABLE_AUX)?;
+
+    let config = config()?;
+    set_config((config | CONFIG_AUX_IRQ) & !CONFIG_AUX_CLOCK_DISABLED)
+}
+
+/// Sends `byte` to the device on the auxiliary port and waits for its `ACK`
+pub fn send_to_aux(byte: u8) -> Result<(), Ps2Error> {
+    command(WRITE_TO_AUX)?;
+    write_data(byte)?;
+    match read_data()? {
+        ACK => Ok(()),
+        other => Err(Ps2Error::NoAck(other)),
+    }
+}
@@ -0,0 +1,49 @@
+use core::arch::x86_64::_rdtsc;
+
+use x86_64::instructions::random::RdRand;
+
+use crate::cmos;
+
+pub use pong_core::Pcg32;
+
+/// A new `Pcg32` for every match, its seed is logged so the match can be played again
+pub fn new_match_rng() -> Pcg32 {
+    let rng = Pcg32::new(hardware_seed());
+    log::info!("Match seed: {:#018x}", rng.seed());
+    rng
+}
+
+/// From RDRAND if the CPU has it (`RdRand::new` asks CPUID),
+/// otherwise from the time stamp counter and the real time clock
+pub fn hardware_seed() -> u64 {
+    match RdRand::new().and_then(|rdrand| rdrand.get_u64()) {
+        Some(seed) => seed,
+        None => fallback_seed(),
+    }
+}
+
+fn fallback_seed() -> u64 {
+    // Cycles since boot differ a little every time, the date covers
+    // machines that count exactly the same cycles each boot
+    let tsc = unsafe { _rdtsc() };
+    // A clock update in the middle of this only makes the seed less predictable
+    let rtc = [
+        cmos::RTC_SECONDS,
+        cmos::RTC_MINUTES,
+        cmos::RTC_HOURS,
+        cmos::RTC_DAY,
+        cmos::RTC_MONTH,
+        cmos::RTC_YEAR,
+    ]
+    .iter()
+    .fold(0u64, |date, &register| date << 8 | cmos::read(register) as u64);
+
+    tsc ^ spread(rtc)
+}
+
+// The date only fills the low 48 bits, this scatters it over all 64 (SplitMix64's finalizer)
+fn spread(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+    value ^ (value >> 31)
+}
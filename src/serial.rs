@@ -0,0 +1,63 @@
+use core::fmt;
+use lazy_static::lazy_static;
+use log::{LevelFilter, Log, Metadata, Record};
+use spin::Mutex;
+use uart_16550::SerialPort;
+
+// COM1, shows up on the host with `qemu-system-x86_64 -serial stdio`
+const COM1: u16 = 0x3f8;
+
+lazy_static! {
+    pub static ref SERIAL1: Mutex<SerialPort> = {
+        let mut serial_port = unsafe { SerialPort::new(COM1) };
+        serial_port.init();
+        Mutex::new(serial_port)
+    };
+}
+
+#[macro_export]
+macro_rules! serial_print {
+    ($($arg:tt)*) => ($crate::serial::_print(format_args!($($arg)*)));
+}
+
+#[macro_export]
+macro_rules! serial_println {
+    () => ($crate::serial_print!("\n"));
+    ($($arg:tt)*) => ($crate::serial_print!("{}\n", format_args!($($arg)*)));
+}
+
+pub fn _print(args: fmt::Arguments) {
+    use core::fmt::Write;
+    use x86_64::instructions::interrupts;
+
+    // Same as for `WRITER`, an interrupt handler might log while we hold the lock
+    interrupts::without_interrupts(|| {
+        SERIAL1.lock().write_fmt(args).unwrap();
+    });
+}
+
+/// Sends everything logged through the `log` macros to COM1,
+/// so diagnostics don't end up on the playfield
+struct SerialLogger;
+
+impl Log for SerialLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            serial_println!("[{:<5}] {}: {}", record.level(), record.target(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: SerialLogger = SerialLogger;
+
+pub fn init_logger() {
+    // Only fails if it was called before, the logger is in place either way
+    log::set_logger(&LOGGER).unwrap_or(());
+    log::set_max_level(LevelFilter::Info);
+}
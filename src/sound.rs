@@ -0,0 +1,187 @@
+use alloc::collections::VecDeque;
+
+use pong_core::StepEvents;
+use x86_64::instructions::port::Port;
+
+use crate::interrupts::set_speaker_frequency;
+
+// Bit 0 lets PIT channel 2 run, bit 1 connects it to the speaker
+const SPEAKER_PORT: u16 = 0x61;
+const SPEAKER_BITS: u8 = 0b11;
+
+fn speaker_on(hz: u32) {
+    set_speaker_frequency(hz);
+    let mut port: Port<u8> = Port::new(SPEAKER_PORT);
+    unsafe {
+        let value = port.read();
+        port.write(value | SPEAKER_BITS);
+    }
+}
+
+/// Turns the speaker off, whatever was playing
+pub fn silence() {
+    let mut port: Port<u8> = Port::new(SPEAKER_PORT);
+    unsafe {
+        let value = port.read();
+        port.write(value & !SPEAKER_BITS);
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Note {
+    pub hz: u32,
+    pub ms: u32,
+}
+
+const fn note(hz: u32, ms: u32) -> Note {
+    Note { hz, ms }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Sound {
+    /// The zone of the bar that was hit, the outer ones sound higher
+    PaddleHit(u8),
+    WallBounce,
+    Point,
+    Win,
+}
+
+// Calls aren't promoted to `'static`, so every sound needs a constant
+const LOW_HIT: [Note; 1] = [note(440, 30)];
+const MIDDLE_HIT: [Note; 1] = [note(587, 30)];
+const HIGH_HIT: [Note; 1] = [note(784, 30)];
+const BOUNCE: [Note; 1] = [note(220, 20)];
+const POINT: [Note; 2] = [note(392, 90), note(262, 140)];
+const WIN: [Note; 4] = [note(523, 100), note(659, 100), note(784, 100), note(1047, 250)];
+
+impl Sound {
+    fn notes(&self) -> &'static [Note] {
+        match self {
+            Sound::PaddleHit(0) => &LOW_HIT,
+            Sound::PaddleHit(1) => &MIDDLE_HIT,
+            Sound::PaddleHit(_) => &HIGH_HIT,
+            Sound::WallBounce => &BOUNCE,
+            Sound::Point => &POINT,
+            Sound::Win => &WIN,
+        }
+    }
+}
+
+// Enough for a point and the win right after it,
+// anything beyond that would only lag behind the game
+const MAX_QUEUED: usize = 8;
+
+/// Notes waiting for the speaker. Nothing in here waits for a note to end,
+/// `tick` moves on to the next one once it's time.
+pub struct SoundQueue {
+    notes: VecDeque<Note>,
+    playing: Option<Note>,
+    left_ms: u32,
+}
+
+impl Default for SoundQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SoundQueue {
+    pub fn new() -> Self {
+        Self {
+            notes: VecDeque::with_capacity(MAX_QUEUED),
+            playing: None,
+            left_ms: 0,
+        }
+    }
+
+    /// Queues `sound` after everything that is already queued
+    pub fn play(&mut self, sound: Sound) {
+        sound.notes().iter().for_each(|&note| {
+            if self.notes.len() < MAX_QUEUED {
+                self.notes.push_back(note);
+            }
+        });
+    }
+
+    /// Queues a sound for everything that happened in a `GameState::step`
+    pub fn play_events(&mut self, events: &StepEvents) {
+        if let Some(zone) = events.paddle_hit {
+            self.play(Sound::PaddleHit(zone));
+        }
+        if events.wall_bounce {
+            self.play(Sound::WallBounce);
+        }
+        if events.scored.is_some() {
+            self.play(Sound::Point);
+        }
+        if events.winner.is_some() {
+            self.play(Sound::Win);
+        }
+    }
+
+    /// Call on every timer tick, `ms` is the time since the last one
+    pub fn tick(&mut self, ms: u32) {
+        if self.playing.is_some() {
+            self.left_ms = self.left_ms.saturating_sub(ms);
+            if self.left_ms > 0 {
+                return;
+            }
+        }
+
+        match self.notes.pop_front() {
+            Some(note) => {
+                speaker_on(note.hz);
+                self.playing = Some(note);
+                self.left_ms = note.ms;
+            }
+            None => {
+                if self.playing.take().is_some() {
+                    silence();
+                }
+            }
+        }
+    }
+
+    /// Drops everything queued and turns the speaker off
+    pub fn stop(&mut self) {
+        self.notes.clear();
+        self.playing = None;
+        silence();
+    }
+
+    pub fn playing(&self) -> Option<Note> {
+        self.playing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn notes_play_one_after_another() {
+        let mut queue = SoundQueue::new();
+        queue.play(Sound::Point);
+
+        queue.tick(1);
+        assert_eq!(queue.playing(), Some(note(392, 90)));
+        queue.tick(89);
+        assert_eq!(queue.playing(), Some(note(392, 90)));
+        queue.tick(1);
+        assert_eq!(queue.playing(), Some(note(262, 140)));
+        queue.tick(140);
+        assert_eq!(queue.playing(), None);
+    }
+
+    #[test_case]
+    fn stop_drops_the_rest() {
+        let mut queue = SoundQueue::new();
+        queue.play(Sound::Win);
+        queue.tick(1);
+
+        queue.stop();
+        queue.tick(1);
+
+        assert_eq!(queue.playing(), None);
+    }
+}
@@ -2,11 +2,12 @@ use core::{sync::atomic::{AtomicU8, Ordering}};
 
 
 #[repr(u8)]
-#[derive(PartialEq)]
+#[derive(PartialEq, Debug)]
 pub enum StateLocation {
     Menu,
     Running,
-    Paused
+    Paused,
+    GameOver,
 }
 
 pub struct GlobalStateLocation (AtomicU8);
@@ -36,6 +37,7 @@ impl Into<StateLocation> for u8 {
             0 => StateLocation::Menu,
             1 => StateLocation::Running,
             2 => StateLocation::Paused,
+            3 => StateLocation::GameOver,
             _ => panic!("Invalid StateLocation")
         }
     }
@@ -47,6 +49,7 @@ impl From<StateLocation> for u8 {
             StateLocation::Menu => 0,
             StateLocation::Running => 1,
             StateLocation::Paused => 2,
+            StateLocation::GameOver => 3,
         }
     }
 }
@@ -63,6 +66,7 @@ impl From<&AtomicU8> for StateLocation {
             0 => Self::Menu,
             1 => Self::Running,
             2 => Self::Paused,
+            3 => Self::GameOver,
             _ => panic!("Invalid StateLocation")
         }
     }
@@ -0,0 +1,445 @@
+use alloc::format;
+use pc_keyboard::KeyCode;
+use pong_core::bindings::{Action, BindError, ACTIONS};
+
+use crate::keyboard::key_label;
+use crate::vga_buffer::{Color, ColorCode, BUFFER_HEIGHT, BUFFER_WIDTH, ScreenChar, Surface};
+
+use super::{GameConfig, GameState, Player};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MenuPage {
+    Main,
+    Settings,
+    Controls,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MenuEntry {
+    // Main
+    Start,
+    Player1,
+    Player1Color,
+    Player2,
+    Player2Color,
+    Settings,
+    // Settings
+    TargetScore,
+    WinByTwo,
+    BallSpeed,
+    PaddleHeight,
+    PaddleSpeed,
+    Difficulty,
+    Background,
+    Sound,
+    MouseSensitivity,
+    Layout,
+    Controls,
+    // Controls
+    Bind(Action),
+    Back,
+}
+
+const MAIN_ENTRIES: [MenuEntry; 6] = [
+    MenuEntry::Start,
+    MenuEntry::Player1,
+    MenuEntry::Player1Color,
+    MenuEntry::Player2,
+    MenuEntry::Player2Color,
+    MenuEntry::Settings,
+];
+
+const SETTINGS_ENTRIES: [MenuEntry; 12] = [
+    MenuEntry::TargetScore,
+    MenuEntry::WinByTwo,
+    MenuEntry::BallSpeed,
+    MenuEntry::PaddleHeight,
+    MenuEntry::PaddleSpeed,
+    MenuEntry::Difficulty,
+    MenuEntry::Background,
+    MenuEntry::Sound,
+    MenuEntry::MouseSensitivity,
+    MenuEntry::Layout,
+    MenuEntry::Controls,
+    MenuEntry::Back,
+];
+
+const CONTROLS_ENTRIES: [MenuEntry; ACTIONS.len() + 1] = [
+    MenuEntry::Bind(ACTIONS[0]),
+    MenuEntry::Bind(ACTIONS[1]),
+    MenuEntry::Bind(ACTIONS[2]),
+    MenuEntry::Bind(ACTIONS[3]),
+    MenuEntry::Bind(ACTIONS[4]),
+    MenuEntry::Bind(ACTIONS[5]),
+    MenuEntry::Bind(ACTIONS[6]),
+    MenuEntry::Back,
+];
+
+impl MenuPage {
+    pub fn entries(&self) -> &'static [MenuEntry] {
+        match self {
+            MenuPage::Main => &MAIN_ENTRIES,
+            MenuPage::Settings => &SETTINGS_ENTRIES,
+            MenuPage::Controls => &CONTROLS_ENTRIES,
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            MenuPage::Main => "Pong!",
+            MenuPage::Settings => "Settings",
+            MenuPage::Controls => "Controls",
+        }
+    }
+}
+
+impl MenuEntry {
+    fn label(&self) -> &'static str {
+        match self {
+            MenuEntry::Start => "Start",
+            MenuEntry::Player1 => "Player 1",
+            MenuEntry::Player1Color => "Player 1 color",
+            MenuEntry::Player2 => "Player 2",
+            MenuEntry::Player2Color => "Player 2 color",
+            MenuEntry::Settings => "Settings",
+            MenuEntry::TargetScore => "Target score",
+            MenuEntry::WinByTwo => "Win by two",
+            MenuEntry::BallSpeed => "Ball speed",
+            MenuEntry::PaddleHeight => "Paddle height",
+            MenuEntry::PaddleSpeed => "Paddle speed",
+            MenuEntry::Difficulty => "AI difficulty",
+            MenuEntry::Background => "Background",
+            MenuEntry::Sound => "Sound",
+            MenuEntry::MouseSensitivity => "Mouse sensitivity",
+            MenuEntry::Layout => "Keyboard layout",
+            MenuEntry::Controls => "Controls",
+            MenuEntry::Bind(action) => action.name(),
+            MenuEntry::Back => "Back",
+        }
+    }
+}
+
+/// What the game has to do after the menu handled a key
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MenuAction {
+    None,
+    Redraw,
+    StartGame,
+}
+
+pub struct Menu {
+    page: MenuPage,
+    cursor: usize,
+    // The action waiting for a key on the controls page
+    binding: Option<Action>,
+    // The last key pressed for it, and why it wasn't taken
+    refused: Option<(KeyCode, BindError)>,
+}
+
+impl Default for Menu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Menu {
+    pub fn new() -> Self {
+        Self {
+            page: MenuPage::Main,
+            cursor: 0,
+            binding: None,
+            refused: None,
+        }
+    }
+
+    pub fn page(&self) -> MenuPage {
+        self.page
+    }
+
+    pub fn selected(&self) -> MenuEntry {
+        self.page.entries()[self.cursor]
+    }
+
+    pub fn binding(&self) -> Option<Action> {
+        self.binding
+    }
+
+    pub fn refused(&self) -> Option<(KeyCode, BindError)> {
+        self.refused
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode, config: &mut GameConfig) -> MenuAction {
+        // Any key but Escape can be bound, even the ones the menu uses.
+        // Escape leaves the binding as it was
+        if let Some(action) = self.binding {
+            match code {
+                KeyCode::Escape => {
+                    self.binding = None;
+                    self.refused = None;
+                }
+                _ => self.bind(action, code, config),
+            }
+            return MenuAction::Redraw;
+        }
+
+        match code {
+            KeyCode::W | KeyCode::ArrowUp => {
+                let len = self.page.entries().len();
+                self.cursor = (self.cursor + len - 1) % len;
+                MenuAction::Redraw
+            }
+            KeyCode::S | KeyCode::ArrowDown => {
+                self.cursor = (self.cursor + 1) % self.page.entries().len();
+                MenuAction::Redraw
+            }
+            KeyCode::A | KeyCode::ArrowLeft => {
+                edit(self.selected(), false, config);
+                MenuAction::Redraw
+            }
+            KeyCode::D | KeyCode::ArrowRight => {
+                edit(self.selected(), true, config);
+                MenuAction::Redraw
+            }
+            KeyCode::Spacebar | KeyCode::Enter => self.activate(config),
+            KeyCode::Escape if self.page != MenuPage::Main => {
+                self.back();
+                MenuAction::Redraw
+            }
+            _ => MenuAction::None,
+        }
+    }
+
+    fn activate(&mut self, config: &mut GameConfig) -> MenuAction {
+        match self.selected() {
+            MenuEntry::Start => MenuAction::StartGame,
+            MenuEntry::Settings => {
+                self.page = MenuPage::Settings;
+                self.cursor = 0;
+                MenuAction::Redraw
+            }
+            MenuEntry::Controls => {
+                self.page = MenuPage::Controls;
+                self.cursor = 0;
+                MenuAction::Redraw
+            }
+            MenuEntry::Bind(action) => {
+                self.binding = Some(action);
+                MenuAction::Redraw
+            }
+            MenuEntry::Back => {
+                self.back();
+                MenuAction::Redraw
+            }
+            // Confirming a value works just like increasing it
+            entry => {
+                edit(entry, true, config);
+                MenuAction::Redraw
+            }
+        }
+    }
+
+    // To the page this one was opened from, with the entry that opened it selected
+    fn back(&mut self) {
+        let (page, entry) = match self.page {
+            MenuPage::Controls => (MenuPage::Settings, MenuEntry::Controls),
+            MenuPage::Settings | MenuPage::Main => (MenuPage::Main, MenuEntry::Settings),
+        };
+        self.page = page;
+        self.cursor = page
+            .entries()
+            .iter()
+            .position(|&e| e == entry)
+            .unwrap_or(0);
+    }
+
+    // Keeps waiting for another key if `code` can't be taken.
+    // Pressing the key the action already has leaves it as it was.
+    fn bind(&mut self, action: Action, code: KeyCode, config: &mut GameConfig) {
+        match config.bind(action, code) {
+            Ok(()) => {
+                self.binding = None;
+                self.refused = None;
+            }
+            Err(error) => self.refused = Some((code, error)),
+        }
+    }
+}
+
+fn edit(entry: MenuEntry, forward: bool, config: &mut GameConfig) {
+    match entry {
+        MenuEntry::Player1 => config.toggle_controller(Player::Player1),
+        MenuEntry::Player2 => config.toggle_controller(Player::Player2),
+        MenuEntry::Player1Color => cycle_color(config, Player::Player1, forward),
+        MenuEntry::Player2Color => cycle_color(config, Player::Player2, forward),
+        MenuEntry::TargetScore if forward => config.next_target_score(),
+        MenuEntry::TargetScore => config.previous_target_score(),
+        MenuEntry::WinByTwo => config.toggle_win_by_two(),
+        MenuEntry::BallSpeed if forward => config.set_ball_speed(config.ball_speed() + 1),
+        MenuEntry::BallSpeed => config.set_ball_speed(config.ball_speed() - 1),
+        MenuEntry::PaddleHeight if forward => config.set_paddle_height(config.paddle_height() + 2),
+        MenuEntry::PaddleHeight => config.set_paddle_height(config.paddle_height() - 2),
+        MenuEntry::PaddleSpeed if forward => config.set_paddle_speed(config.paddle_speed() + 1),
+        MenuEntry::PaddleSpeed => config.set_paddle_speed(config.paddle_speed() - 1),
+        MenuEntry::Difficulty if forward => config.next_difficulty(),
+        MenuEntry::Difficulty => config.previous_difficulty(),
+        // The screen follows in `GameLoop::handle_menu_key`
+        MenuEntry::Background if forward => config.next_background(),
+        MenuEntry::Background => config.previous_background(),
+        MenuEntry::Sound => config.toggle_muted(),
+        MenuEntry::MouseSensitivity if forward => config.set_mouse_sensitivity(config.mouse_sensitivity() + 1),
+        MenuEntry::MouseSensitivity => config.set_mouse_sensitivity(config.mouse_sensitivity() - 1),
+        // The keyboard follows in `GameLoop::handle_menu_key`
+        MenuEntry::Layout if forward => config.next_layout(),
+        MenuEntry::Layout => config.previous_layout(),
+        MenuEntry::Start | MenuEntry::Settings | MenuEntry::Controls | MenuEntry::Bind(_) | MenuEntry::Back => {}
+    }
+}
+
+fn cycle_color(config: &mut GameConfig, player: Player, forward: bool) {
+    let color = config.player_color(player);
+    let color = if forward {
+        color.next_color()
+    } else {
+        color.previous_color()
+    };
+    config.set_color(color, player);
+}
+
+const TITLE_ROW: usize = 3;
+const FIRST_ENTRY_ROW: usize = 5;
+const HIGH_SCORES_ROW: usize = 18;
+const HELP_ROW: usize = 21;
+
+pub fn render_menu_text(writer: &mut impl Surface, state: &GameState, menu: &Menu) {
+    clear_menu_text(writer);
+    let config = &state.config;
+
+    let halfway_point = BUFFER_WIDTH / 2;
+    let label_column = halfway_point - 20;
+    let value_column = halfway_point;
+
+    let title = menu.page().title();
+    writer.write_string_at_pos(TITLE_ROW, halfway_point - title.len() / 2, title);
+
+    let entries = menu.page().entries();
+    // A blank row between entries, unless there are too many of them
+    let spacing = if 2 * entries.len() <= HELP_ROW - FIRST_ENTRY_ROW { 2 } else { 1 };
+
+    entries.iter().enumerate().for_each(|(i, &entry)| {
+        let row = FIRST_ENTRY_ROW + spacing * i;
+        if entry == menu.selected() {
+            writer.write_string_at_pos(row, label_column - 2, ">");
+        }
+        writer.write_string_at_pos(row, label_column, entry.label());
+
+        let value = match entry {
+            MenuEntry::Player1 => EntryValue::Text(config.controller(Player::Player1).name()),
+            MenuEntry::Player2 => EntryValue::Text(config.controller(Player::Player2).name()),
+            MenuEntry::Player1Color => EntryValue::Swatch(config.player_color(Player::Player1)),
+            MenuEntry::Player2Color => EntryValue::Swatch(config.player_color(Player::Player2)),
+            MenuEntry::TargetScore => EntryValue::Number(config.target_score()),
+            MenuEntry::WinByTwo => EntryValue::Text(if config.win_by_two() { "Yes" } else { "No" }),
+            MenuEntry::BallSpeed => EntryValue::Number(config.ball_speed()),
+            MenuEntry::PaddleHeight => EntryValue::Number(config.paddle_height()),
+            MenuEntry::PaddleSpeed => EntryValue::Text(PADDLE_SPEEDS[(config.paddle_speed() - 1) as usize]),
+            MenuEntry::Difficulty => EntryValue::Text(config.difficulty().name()),
+            MenuEntry::Background => EntryValue::Swatch(config.background()),
+            MenuEntry::Sound => EntryValue::Text(if config.muted() { "Off" } else { "On" }),
+            MenuEntry::MouseSensitivity => EntryValue::Number(config.mouse_sensitivity()),
+            MenuEntry::Layout => EntryValue::Text(config.layout().name()),
+            MenuEntry::Bind(action) => {
+                // Nothing to step through, so no arrows
+                if menu.binding() == Some(action) {
+                    writer.write_string_at_pos(row, value_column + 2, "...");
+                } else {
+                    let key = key_label(config.layout(), config.bindings().key(action));
+                    writer.write_string_at_pos(row, value_column + 2, &key);
+                }
+                return;
+            }
+            MenuEntry::Start | MenuEntry::Settings | MenuEntry::Controls | MenuEntry::Back => return,
+        };
+
+        writer.write_string_at_pos(row, value_column, "<");
+        match value {
+            EntryValue::Text(text) => writer.write_string_at_pos(row, value_column + 2, text),
+            EntryValue::Number(n) => writer.write_bytes_at_pos(row, value_column + 2, &two_digits(n)),
+            EntryValue::Swatch(color) => (0..SWATCH_WIDTH).for_each(|i| {
+                writer.write_screen_char_at_pos(row, value_column + 2 + i, ScreenChar {
+                    ascii_character: 0xdb,
+                    color_code: ColorCode::new(color, config.background()),
+                });
+            }),
+        }
+        writer.write_string_at_pos(row, value_column + 10, ">");
+    });
+
+    if menu.page() == MenuPage::Main {
+        let high_scores = format!(
+            "Longest rally: {}   Wins against the CPU: {}",
+            state.high_scores.longest_rally, state.high_scores.wins_against_cpu,
+        );
+        write_centered(writer, HIGH_SCORES_ROW, &high_scores);
+    }
+
+    if let Some(action) = menu.binding() {
+        let prompt = format!("Press a key for {}, ESC to cancel", action.name());
+        write_centered(writer, HELP_ROW, &prompt);
+        if let Some((key, error)) = menu.refused() {
+            let key = key_label(config.layout(), key);
+            let refused = match error {
+                BindError::Taken(other) => format!("{} is already used for {}, try another one", key, other.name()),
+                BindError::CantBeSaved => format!("{} can't be used, try another one", key),
+            };
+            write_centered(writer, HELP_ROW + 1, &refused);
+        }
+    } else {
+        writer.write_string_at_pos(HELP_ROW, halfway_point - 27, "W S / Up Down: select    A D / Left Right: change");
+        writer.write_string_at_pos(HELP_ROW + 1, halfway_point - 22, "SPACE / ENTER: confirm    ESC: back");
+    }
+
+    let label = |action| key_label(config.layout(), config.bindings().key(action));
+    let in_game = format!(
+        "In game   P1: {} {}   P2: {} {}   {}: pause   {}: menu",
+        label(Action::P1Up),
+        label(Action::P1Down),
+        label(Action::P2Up),
+        label(Action::P2Down),
+        label(Action::Pause),
+        label(Action::Menu),
+    );
+    write_centered(writer, HELP_ROW + 3, &in_game);
+}
+
+fn write_centered(writer: &mut impl Surface, row: usize, text: &str) {
+    writer.write_string_at_pos(row, (BUFFER_WIDTH / 2).saturating_sub(text.len() / 2), text);
+}
+
+// Everything but the score row and the columns the bars live in
+pub fn clear_menu_text(writer: &mut impl Surface) {
+    (1..BUFFER_HEIGHT).for_each(|row| {
+        (2..BUFFER_WIDTH - 2).for_each(|col| {
+            writer.write_byte_at_pos(row, col, b' ');
+        })
+    })
+}
+
+enum EntryValue {
+    Text(&'static str),
+    Number(u8),
+    Swatch(Color),
+}
+
+const SWATCH_WIDTH: usize = 5;
+
+// Cells per tick for each `paddle_speed`
+const PADDLE_SPEEDS: [&str; 8] = ["0.25", "0.50", "0.75", "1.00", "1.25", "1.50", "1.75", "2.00"];
+
+// Left aligned, the unused digit is a space
+fn two_digits(n: u8) -> [u8; 2] {
+    if n < 10 {
+        [b'0' + n, b' ']
+    } else {
+        [b'0' + (n / 10) % 10, b'0' + n % 10]
+    }
+}
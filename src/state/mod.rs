@@ -1,7 +1,9 @@
 mod game_state;
 mod location;
-mod config;
+mod menu;
 
-pub use config::*;
+pub use pong_core::config::*;
+pub use pong_core::{Player, Position};
 pub use location::*;
-pub use game_state::*;
\ No newline at end of file
+pub use game_state::*;
+pub use menu::*;
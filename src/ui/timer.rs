@@ -1,13 +1,55 @@
-use core::sync::atomic::AtomicU8;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use pong_core::Clock;
+
+use crate::interrupts::TICK_HZ;
+use crate::TIMER;
 
 pub struct Timer {
-    pub number: AtomicU8,
+    // Only ever touched by the timer interrupt
+    pub ticks: AtomicU32,
 }
 
 impl Timer {
     pub fn new() -> Self {
         Self {
-            number: AtomicU8::new(0),
+            ticks: AtomicU32::new(0),
         }
     }
-}
\ No newline at end of file
+
+    pub fn ticks(&self) -> u32 {
+        self.ticks.load(Ordering::Relaxed)
+    }
+}
+
+/// Spreads `rate` updates per second evenly over the timer ticks,
+/// so the game runs at the same speed whatever the tick rate is
+pub struct Accumulator(u32);
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Accumulator {
+    pub fn new() -> Self {
+        Self(0)
+    }
+
+    /// Number of updates that are due after one more of `tick_hz` ticks
+    pub fn advance(&mut self, rate: u32, tick_hz: u32) -> u32 {
+        let accumulated = self.0 + rate;
+        self.0 = accumulated % tick_hz;
+        accumulated / tick_hz
+    }
+}
+
+/// Milliseconds derived from the ticks of the PIT
+pub struct TimerClock;
+
+impl Clock for TimerClock {
+    fn millis(&self) -> u32 {
+        TIMER.ticks().wrapping_mul(1000 / TICK_HZ)
+    }
+}
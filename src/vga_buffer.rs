@@ -32,98 +32,9 @@ pub fn _print(args: fmt::Arguments) {
     });
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-#[repr(u8)]
-#[allow(dead_code)]
-pub enum Color {
-    Black = 0,
-    Blue = 1,
-    Green = 2,
-    Cyan = 3,
-    Red = 4,
-    Magenta = 5,
-    Brown = 6,
-    LightGray = 7,
-    DarkGray = 8,
-    LightBlue = 9,
-    LightGreen = 10,
-    LightCyan = 11,
-    LightRed = 12,
-    Pink = 13,
-    Yellow = 14,
-    White = 15,
-}
-
-impl From<u8> for Color {
-    fn from(n: u8) -> Self {
-        use Color::*;
-        match n {
-            0 => Black,
-            1 => Blue,
-            2 => Green,
-            3 => Cyan,
-            4 => Red,
-            5 => Magenta,
-            6 => Brown,
-            7 => LightGray,
-            8 => DarkGray,
-            9 => LightBlue,
-            10 => LightGreen,
-            11 => LightCyan,
-            12 => LightRed,
-            13 => Pink,
-            14 => Yellow,
-            15 => White,
-            _ => panic!("Invalid Color")
-        }
-    }
-}
-
-impl Color {
-    pub fn next_color(&self) -> Color {
-        let mut val = *self as u8;
-        if val > 14 {
-            val = 1;
-        }
-        val += 1;
-        val.into()
-    }
-
-    pub fn previous_color(&self) -> Color {
-        let mut val = *self as u8;
-        if val < 2 {
-            val = 15;
-        }
-
-        val -= 1;
-
-        val.into()
-    }
-}
-
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(transparent)]
-pub struct ColorCode(pub u8);
-
-impl ColorCode {
-    pub fn new(foreground: Color, background: Color) -> ColorCode {
-        ColorCode((background as u8) << 4 | (foreground as u8))
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(C)]
-pub struct ScreenChar {
-    pub ascii_character: u8,
-    pub color_code: ColorCode,
-}
-
-// There are 25 rows
-pub const BUFFER_HEIGHT: usize = 25;
-
-// There are 80 columns
-pub const BUFFER_WIDTH: usize = 80;
+// The screen types live in `pong_core`, so the game logic can use them too
+pub use pong_core::screen::{Color, ColorCode, ScreenChar, BUFFER_HEIGHT, BUFFER_WIDTH};
+pub use pong_core::{Frame, Surface};
 
 // Make ScreenChar volatile so read/writes aren't
 // optimized away
@@ -160,18 +71,6 @@ impl Writer {
         }
     }
 
-    pub fn write_byte_at_pos(&mut self, row: usize, col: usize, byte: u8) {
-        let color_code = self.color_code;
-        self.buffer.chars[row][col] = Volatile::new(ScreenChar {
-            ascii_character: byte,
-            color_code,
-        });
-    }
-
-    pub fn write_screen_char_at_pos(&mut self, row: usize, col: usize, screen_char: ScreenChar) {
-        self.buffer.chars[row][col] = Volatile::new(screen_char);
-    }
-
     pub fn write_string(&mut self, s: &str) {
         for byte in s.bytes() {
             match byte {
@@ -183,33 +82,6 @@ impl Writer {
         }
     }
 
-    #[allow(dead_code)]
-    pub fn write_string_at_pos(&mut self, row: usize, mut col: usize, s: &str) {
-        s.bytes().into_iter().for_each(|byte| {
-            match byte {
-            0x20..=0x7e => self.write_byte_at_pos(row, col, byte),
-            _ => self.write_byte_at_pos(row, col, 0xfe),            
-            };
-            col += 1;
-            if col > BUFFER_WIDTH - 1 {
-                col = 0;
-            }
-        });
-
-    
-    }
-
-    #[allow(dead_code)]
-    pub fn write_bytes_at_pos(&mut self, row: usize, mut col: usize, bs: &[u8]) {
-        bs.iter().for_each(|byte| {
-            match byte {
-                0x20..=0x7e => self.write_byte_at_pos(row, col, *byte),
-                _ => self.write_byte_at_pos(row, col, 0xfe),
-            }
-            col += 1;
-        });
-    }
-
     fn new_line(&mut self) {
         for row in 1..BUFFER_HEIGHT {
             for col in 0..BUFFER_WIDTH {
@@ -231,6 +103,18 @@ impl Writer {
         }
     }
 
+    /// Changes the background of everything on screen and of everything written from now on
+    pub fn set_background(&mut self, background: Color) {
+        self.color_code = self.color_code.with_background(background);
+        for row in 0..BUFFER_HEIGHT {
+            for col in 0..BUFFER_WIDTH {
+                let mut character = self.buffer.chars[row][col].read();
+                character.color_code = character.color_code.with_background(background);
+                self.buffer.chars[row][col].write(character);
+            }
+        }
+    }
+
     #[allow(dead_code)]
     pub fn set_at_pos(&mut self, row: usize, col: usize, screen_char: ScreenChar) {
         self.buffer.chars[row][col].write(screen_char)
@@ -250,6 +134,79 @@ impl Writer {
     }
 }
 
+impl Surface for Writer {
+    fn write_screen_char_at_pos(&mut self, row: usize, col: usize, screen_char: ScreenChar) {
+        self.buffer.chars[row][col].write(screen_char);
+    }
+
+    fn read_screen_char_at_pos(&self, row: usize, col: usize) -> ScreenChar {
+        self.buffer.chars[row][col].read()
+    }
+
+    fn color_code(&self) -> ColorCode {
+        self.color_code
+    }
+}
+
+/// Off-screen copy of the screen everything is drawn to first.
+/// `present` then puts only the cells that changed since the last time into
+/// the VGA buffer, so half drawn frames are never visible.
+pub struct BackBuffer {
+    back: Frame,
+    // What the VGA buffer holds, as far as we know
+    front: Frame,
+}
+
+impl Default for BackBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BackBuffer {
+    /// Starts out with whatever is on screen right now
+    pub fn new() -> Self {
+        let front = Frame::copy_of(&*WRITER.lock());
+        Self {
+            back: front.clone(),
+            front,
+        }
+    }
+
+    pub fn present(&mut self) {
+        let mut writer = WRITER.lock();
+        for (row, col, screen_char) in self.back.changes(&self.front) {
+            writer.write_screen_char_at_pos(row, col, screen_char);
+        }
+        self.front = self.back.clone();
+    }
+
+    /// Changes the background of the whole screen and everything drawn from now on
+    pub fn set_background(&mut self, background: Color) {
+        if self.back.color_code() == self.back.color_code().with_background(background) {
+            return;
+        }
+        self.back.set_background(background);
+        // Also recolors the screen itself, so the front buffer has to follow
+        WRITER.lock().set_background(background);
+        self.front.set_background(background);
+    }
+}
+
+impl Surface for BackBuffer {
+    fn write_screen_char_at_pos(&mut self, row: usize, col: usize, screen_char: ScreenChar) {
+        self.back.write_screen_char_at_pos(row, col, screen_char);
+    }
+
+    fn read_screen_char_at_pos(&self, row: usize, col: usize) -> ScreenChar {
+        self.back.read_screen_char_at_pos(row, col)
+    }
+
+    fn color_code(&self) -> ColorCode {
+        self.back.color_code()
+    }
+}
+
 impl fmt::Write for Writer {
     fn write_str(&mut self, s: &str) -> fmt::Result {
         self.write_string(s);
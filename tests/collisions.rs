@@ -0,0 +1,235 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(pong::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+use core::panic::PanicInfo;
+
+use pong_core::game_state::reset_ball;
+use pong_core::pongball::{BallPosition, Fixed, PongBall, Speed};
+use pong_core::pongbar::{Key, PongBar};
+use pong_core::screen::BUFFER_HEIGHT;
+use pong_core::{Clock, GameConfig, GameState, Player, Rng, StepEvents};
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    test_main();
+    pong::hlt_loop();
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    pong::test_panic_handler(info)
+}
+
+// Always serves the same way
+struct FixedRng(u8);
+
+impl Rng for FixedRng {
+    fn next_u8(&mut self) -> u8 {
+        self.0
+    }
+}
+
+struct StoppedClock;
+
+impl Clock for StoppedClock {
+    fn millis(&self) -> u32 {
+        0
+    }
+}
+
+type TestState = GameState<FixedRng, StoppedClock>;
+
+fn game() -> TestState {
+    GameState::new(GameConfig::default(), FixedRng(0), StoppedClock)
+}
+
+// Starts the ball at (x, y) and makes one step of (dx, dy)
+fn place_ball(state: &mut TestState, x: f64, y: f64, dx: f64, dy: f64) {
+    state.ball.position = BallPosition { x: Fixed::from_num(x), y: Fixed::from_num(y) };
+    state.ball.speed = Speed { dx: Fixed::from_num(dx), dy: Fixed::from_num(dy) };
+    state.ball.move_ball();
+}
+
+// Puts the ball in front of `player`'s bar, `rows` below its middle
+// (above for negative `rows`), moving into it
+fn ball_at_bar(state: &mut TestState, player: Player, rows: i8, dy: f64) {
+    let (x, dx, bar) = match player {
+        Player::Player1 => (2.5, -1.0, &state.player1),
+        Player::Player2 => (77.5, 1.0, &state.player2),
+    };
+    let y = bar.position.y as f64 + rows as f64 + 0.5;
+    place_ball(state, x, y, dx, dy);
+}
+
+// Hits `player`'s bar `rows` off its middle and checks the ball bounced off `zone`
+fn hit_zone(player: Player, rows: i8, zone: u8) -> TestState {
+    let mut state = game();
+    ball_at_bar(&mut state, player, rows, 0.5);
+
+    assert_eq!(state.eval_collisions(), StepEvents { paddle_hit: Some(zone), ..StepEvents::default() });
+    state
+}
+
+// `dx` after a hit, pointing away from `player`'s bar
+fn away_from(player: Player, dx: f64) -> Fixed {
+    match player {
+        Player::Player1 => Fixed::from_num(dx),
+        Player::Player2 => Fixed::from_num(-dx),
+    }
+}
+
+const PLAYERS: [Player; 2] = [Player::Player1, Player::Player2];
+
+//////////////////////////////////////////////////////
+//                  Paddle zones                    //
+//////////////////////////////////////////////////////
+
+#[test_case]
+fn zone_0_slows_the_ball_down() {
+    for &player in &PLAYERS {
+        let state = hit_zone(player, 0, 0);
+        assert_eq!(state.ball.speed.dx, away_from(player, 0.85));
+        assert_eq!(state.ball.speed.dy, Fixed::from_num(0.5 * 0.9));
+    }
+}
+
+#[test_case]
+fn zone_1_speeds_the_ball_up() {
+    for &player in &PLAYERS {
+        for &rows in &[1, -1] {
+            let state = hit_zone(player, rows, 1);
+            assert_eq!(state.ball.speed.dx, away_from(player, 1.15));
+            assert_eq!(state.ball.speed.dy, Fixed::from_num(0.5));
+        }
+    }
+}
+
+#[test_case]
+fn zone_2_steepens_the_ball() {
+    for &player in &PLAYERS {
+        for &rows in &[2, -2] {
+            let state = hit_zone(player, rows, 2);
+            assert_eq!(state.ball.speed.dx, away_from(player, 1.1));
+            assert_eq!(state.ball.speed.dy, Fixed::from_num(0.5 * 1.35));
+        }
+    }
+}
+
+#[test_case]
+fn fast_ball_cant_skip_the_bar() {
+    let mut state = game();
+    let y = state.player1.position.y as f64 + 0.5;
+    // Would go from the middle of the field straight into the wall behind the bar
+    place_ball(&mut state, 8.0, y, -10.0, 0.0);
+
+    assert_eq!(state.eval_collisions(), StepEvents { paddle_hit: Some(0), ..StepEvents::default() });
+    assert_eq!(state.ball.speed.dx, Fixed::from_num(10) * Fixed::from_num(0.85));
+    assert!(state.ball.position.x > Fixed::from_num(2));
+}
+
+#[test_case]
+fn fast_ball_hits_the_bar_after_a_wall() {
+    let mut state = game();
+    let half_height = state.config.paddle_half_height();
+    state.player2.position.y = BUFFER_HEIGHT as u8 - 1 - half_height;
+    // Bounces off the bottom wall on its way into the bar
+    place_ball(&mut state, 70.0, 22.5, 10.0, 3.0);
+
+    assert_eq!(state.eval_collisions(), StepEvents { paddle_hit: Some(1), wall_bounce: true, ..StepEvents::default() });
+    assert!(state.ball.speed.dx.is_negative());
+    assert!(state.ball.speed.dy.is_negative());
+    assert!(state.ball.position.x < Fixed::from_num(78));
+}
+
+//////////////////////////////////////////////////////
+//                  Scoring                         //
+//////////////////////////////////////////////////////
+
+#[test_case]
+fn left_wall_scores_for_player2() {
+    let mut state = game();
+    state.player1.position.y = 5;
+    place_ball(&mut state, 0.5, 20.5, -1.0, 0.0);
+
+    let events = state.eval_collisions();
+
+    assert_eq!(events.scored, Some(Player::Player2));
+    assert_eq!(state.score, (0, 1));
+}
+
+#[test_case]
+fn right_wall_scores_for_player1() {
+    let mut state = game();
+    state.player2.position.y = 5;
+    place_ball(&mut state, 78.5, 20.5, 1.0, 0.0);
+
+    let events = state.eval_collisions();
+
+    assert_eq!(events.scored, Some(Player::Player1));
+    assert_eq!(state.score, (1, 0));
+}
+
+//////////////////////////////////////////////////////
+//                  Walls                           //
+//////////////////////////////////////////////////////
+
+#[test_case]
+fn top_wall_bounces() {
+    let mut state = game();
+    place_ball(&mut state, 40.5, 1.5, 1.0, -1.0);
+
+    assert_eq!(state.eval_collisions(), StepEvents { wall_bounce: true, ..StepEvents::default() });
+    assert_eq!(state.ball.speed.dy, Fixed::from_num(1));
+}
+
+#[test_case]
+fn bottom_wall_bounces() {
+    let mut state = game();
+    place_ball(&mut state, 40.5, 23.5, 1.0, 1.0);
+
+    assert_eq!(state.eval_collisions(), StepEvents { wall_bounce: true, ..StepEvents::default() });
+    assert_eq!(state.ball.speed.dy, Fixed::from_num(-1));
+    assert_eq!(state.ball.position.y, Fixed::from_num(23.5));
+}
+
+//////////////////////////////////////////////////////
+//                  Resets                          //
+//////////////////////////////////////////////////////
+
+#[test_case]
+fn reset_players_restores_the_bars() {
+    let mut state = game();
+    state.player1.position.y = 3;
+    state.player1.press(Key::Up);
+    state.player2.position.y = 20;
+    state.player2.press(Key::Down);
+
+    state.reset_players();
+
+    assert_eq!(state.player1.position, PongBar::new(Player::Player1).position);
+    assert_eq!(state.player2.position, PongBar::new(Player::Player2).position);
+    assert!(matches!(state.player1.held_direction(), Key::None));
+    assert!(matches!(state.player2.held_direction(), Key::None));
+}
+
+#[test_case]
+fn reset_ball_serves_from_the_middle() {
+    let mut rng = FixedRng(0);
+    let mut ball = PongBall::new(3, &mut rng);
+    ball.position = BallPosition { x: Fixed::from_num(70.5), y: Fixed::from_num(3.5) };
+    ball.speed = Speed { dx: Fixed::from_num(4), dy: Fixed::from_num(-2) };
+
+    reset_ball(&mut ball, 3, &mut rng);
+
+    let fresh = PongBall::new(3, &mut rng);
+    assert_eq!(ball.position.x, BallPosition::default().x);
+    assert_eq!(ball.position.y, BallPosition::default().y);
+    // Old position is kept, so the cell can still be erased
+    assert_eq!(ball.last_pos.x, Fixed::from_num(70.5));
+    assert_eq!(ball.last_pos.y, Fixed::from_num(3.5));
+    assert_eq!(ball.speed.dx, fresh.speed.dx);
+    assert_eq!(ball.speed.dy, fresh.speed.dy);
+}